@@ -0,0 +1,233 @@
+use libaeron_driver_sys as aeron_driver;
+
+use std::collections::HashMap;
+use std::ffi::{c_char, c_void, CStr};
+use std::io;
+use std::sync::Arc;
+
+use crate::common;
+use crate::common::CallbackRegistry;
+
+/// Called on each agent thread the driver starts, with the agent's role name.
+pub type AgentOnStart = dyn Fn(&str) + Send + Sync;
+
+static ON_START: CallbackRegistry<AgentOnStart> = CallbackRegistry::new();
+
+const THREAD_NAME_MAX_LEN: usize = 15;
+
+/// CPUs a Linux `cpu_set_t` can hold.
+const CPU_SETSIZE: usize = 1024;
+
+/// Strips the decoration the driver adds around role names, e.g. `[sender]` or
+/// `driver-conductor`, leaving `sender` or `conductor`.
+pub fn normalize_role_name(role_name: &str) -> &str {
+    let role_name = role_name.trim_start_matches('[').trim_end_matches(']');
+    role_name.strip_prefix("driver-").unwrap_or(role_name)
+}
+
+/// Pins the calling thread to `cpus`.
+#[cfg(target_os = "linux")]
+pub fn pin_current_thread(cpus: &[usize]) -> io::Result<()> {
+    if let Some(cpu) = cpus.iter().find(|&&cpu| cpu >= CPU_SETSIZE) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("CPU {cpu} is beyond the {CPU_SETSIZE} a CPU set can hold"),
+        ));
+    }
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        libc::CPU_ZERO(&mut set);
+        for &cpu in cpus {
+            libc::CPU_SET(cpu, &mut set);
+        }
+        if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn pin_current_thread(_cpus: &[usize]) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "thread affinity is only supported on Linux",
+    ))
+}
+
+/// Names the calling thread, truncating the name to the 15 bytes Linux allows.
+#[cfg(target_os = "linux")]
+pub fn set_current_thread_name(name: &str) -> io::Result<()> {
+    let mut end = name.len().min(THREAD_NAME_MAX_LEN);
+    while !name.is_char_boundary(end) {
+        end -= 1;
+    }
+    let name = std::ffi::CString::new(&name[..end])
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+    let result = unsafe { libc::pthread_setname_np(libc::pthread_self(), name.as_ptr()) };
+    if result != 0 {
+        return Err(io::Error::from_raw_os_error(result));
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn set_current_thread_name(_name: &str) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "thread naming is only supported on Linux",
+    ))
+}
+
+/// CPUs to pin each driver agent thread to, keyed by role name.
+///
+/// Roles are matched after `normalize_role_name`, so `conductor`, `sender` and `receiver`
+/// select the threads of `DEDICATED` mode, and `sender-receiver` the shared thread of
+/// `SHARED_NETWORK` mode. Threads are also named `aeron-<role>` unless disabled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AgentAffinity {
+    cpus: HashMap<String, Vec<usize>>,
+    name_threads: bool,
+}
+
+impl Default for AgentAffinity {
+    fn default() -> Self {
+        Self {
+            cpus: HashMap::new(),
+            name_threads: true,
+        }
+    }
+}
+
+impl AgentAffinity {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_cpus(mut self, role: &str, cpus: &[usize]) -> Self {
+        self.cpus.insert(role.to_string(), cpus.to_vec());
+        self
+    }
+
+    pub fn with_thread_names(mut self, name_threads: bool) -> Self {
+        self.name_threads = name_threads;
+        self
+    }
+
+    /// Parses a map such as `conductor=1,sender=2,receiver=3-4`, where each role is given
+    /// a CPU or an inclusive range of CPUs. CPUs must be below 1024 and ranges ascending.
+    pub fn parse(map: &str) -> common::Result<Self> {
+        let mut affinity = Self::new();
+        for entry in map
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+        {
+            let (role, cpus) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("expected ROLE=CPUS in agent affinity entry {entry:?}"))?;
+            let parse_cpu = |cpu: &str| {
+                cpu.trim()
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&cpu| cpu < CPU_SETSIZE)
+                    .ok_or_else(|| format!("invalid CPU {cpu:?} in agent affinity entry {entry:?}"))
+            };
+            let cpus = match cpus.split_once('-') {
+                Some((first, last)) => {
+                    let (first, last) = (parse_cpu(first)?, parse_cpu(last)?);
+                    if first > last {
+                        return Err(format!(
+                            "reversed CPU range in agent affinity entry {entry:?}"
+                        )
+                        .into());
+                    }
+                    (first..=last).collect()
+                }
+                None => vec![parse_cpu(cpus)?],
+            };
+            affinity.cpus.insert(role.trim().to_string(), cpus);
+        }
+        Ok(affinity)
+    }
+
+    pub fn cpus(&self, role_name: &str) -> Option<&[usize]> {
+        self.cpus
+            .get(normalize_role_name(role_name))
+            .map(Vec::as_slice)
+    }
+
+    /// Applies the affinity to the calling agent thread. Failures are reported on stderr
+    /// since the driver gives the start hook no way to fail.
+    pub fn on_start(&self, role_name: &str) {
+        let role = normalize_role_name(role_name);
+        if self.name_threads {
+            if let Err(error) = set_current_thread_name(&format!("aeron-{role}")) {
+                eprintln!("Failed to name {role} agent thread: {error}");
+            }
+        }
+        if let Some(cpus) = self.cpus(role_name) {
+            if let Err(error) = pin_current_thread(cpus) {
+                eprintln!("Failed to pin {role} agent thread to CPUs {cpus:?}: {error}");
+            }
+        }
+    }
+}
+
+pub(crate) fn set_on_start(
+    context: *mut aeron_driver::aeron_driver_context_t,
+    on_start: Arc<AgentOnStart>,
+) -> i32 {
+    ON_START.insert(context, on_start);
+    // The context is the hook's state so the closure can be found again
+    unsafe {
+        aeron_driver::aeron_driver_context_set_agent_on_start_function(
+            context,
+            Some(on_start_fn),
+            context as *mut c_void,
+        )
+    }
+}
+
+pub(crate) fn remove_on_start(context: *mut aeron_driver::aeron_driver_context_t) {
+    ON_START.remove(context);
+}
+
+unsafe extern "C" fn on_start_fn(state: *mut c_void, role_name: *const c_char) {
+    let Some(on_start) = ON_START.get(state as *const aeron_driver::aeron_driver_context_t) else {
+        return;
+    };
+    let role_name = if role_name.is_null() {
+        "".into()
+    } else {
+        CStr::from_ptr(role_name).to_string_lossy()
+    };
+    on_start(&role_name);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_affinity_map() {
+        let affinity = AgentAffinity::parse("conductor=1, sender=2,receiver=3-5").unwrap();
+        assert_eq!(affinity.cpus("[conductor]"), Some(&[1][..]));
+        assert_eq!(affinity.cpus("driver-conductor"), Some(&[1][..]));
+        assert_eq!(affinity.cpus("sender"), Some(&[2][..]));
+        assert_eq!(affinity.cpus("receiver"), Some(&[3, 4, 5][..]));
+        assert_eq!(affinity.cpus("sender-receiver"), None);
+
+        assert!(AgentAffinity::parse("conductor").is_err());
+        assert!(AgentAffinity::parse("conductor=x").is_err());
+    }
+
+    #[test]
+    fn reject_cpus_outside_cpu_set() {
+        assert!(AgentAffinity::parse("conductor=1023").is_ok());
+        assert!(AgentAffinity::parse("conductor=1024").is_err());
+        assert!(AgentAffinity::parse("receiver=1020-1024").is_err());
+        assert!(AgentAffinity::parse("receiver=3-1").is_err());
+        assert!(pin_current_thread(&[CPU_SETSIZE]).is_err());
+    }
+}
@@ -0,0 +1,203 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::counters::{
+    CounterEntry, PUBLISHER_POSITION_TYPE_ID, RECEIVER_HWM_TYPE_ID, RECEIVER_POSITION_TYPE_ID,
+    SENDER_LIMIT_TYPE_ID, SENDER_POSITION_TYPE_ID, SUBSCRIBER_POSITION_TYPE_ID,
+};
+use crate::streams::{group_by_stream, StreamPositions};
+
+/// How far one subscriber trails the position it consumes from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SubscriberBacklog {
+    pub registration_id: i64,
+    pub position: i64,
+    /// `rcv-pos - sub-pos`, or `pub-pos - sub-pos` for IPC streams without a receiver.
+    pub lag: Option<i64>,
+}
+
+/// Positions of one publication or image, keyed by its registration id, and the backlogs
+/// between them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct StreamBacklog {
+    pub registration_id: i64,
+    pub session_id: i32,
+    pub stream_id: i32,
+    pub channel: String,
+    pub publisher_position: Option<i64>,
+    pub sender_position: Option<i64>,
+    pub sender_limit: Option<i64>,
+    pub receiver_hwm: Option<i64>,
+    pub receiver_position: Option<i64>,
+    pub subscribers: Vec<SubscriberBacklog>,
+}
+
+impl StreamBacklog {
+    fn new(stream: &StreamPositions, registration_id: i64) -> Self {
+        Self {
+            registration_id,
+            session_id: stream.session_id,
+            stream_id: stream.stream_id,
+            channel: stream.channel.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Data published but not yet sent, `pub-pos - snd-pos`.
+    pub fn publisher_backlog(&self) -> Option<i64> {
+        Some(self.publisher_position? - self.sender_position?)
+    }
+
+    /// Data the sender may still send before flow control blocks it, `snd-lmt - snd-pos`.
+    pub fn sender_window(&self) -> Option<i64> {
+        Some(self.sender_limit? - self.sender_position?)
+    }
+
+    /// Data received but not yet processed in order, `rcv-hwm - rcv-pos`.
+    pub fn receiver_backlog(&self) -> Option<i64> {
+        Some(self.receiver_hwm? - self.receiver_position?)
+    }
+}
+
+/// Groups stream position counters by the registration id of the publication or image they
+/// belong to and computes the backlog at each stage.
+///
+/// Subscriber positions carry the subscription's registration id, so each one is attached to
+/// the image of its stream, or to the publication for IPC streams without an image.
+pub fn stream_backlogs(counters: &[CounterEntry]) -> Vec<StreamBacklog> {
+    let mut backlogs = Vec::new();
+
+    for stream in group_by_stream(counters) {
+        let mut registrations: BTreeMap<i64, StreamBacklog> = BTreeMap::new();
+        let mut subscribers = Vec::new();
+
+        for position in &stream.positions {
+            if position.type_id == SUBSCRIBER_POSITION_TYPE_ID {
+                subscribers.push(position);
+                continue;
+            }
+            let backlog = registrations
+                .entry(position.registration_id)
+                .or_insert_with(|| StreamBacklog::new(&stream, position.registration_id));
+
+            match position.type_id {
+                PUBLISHER_POSITION_TYPE_ID => backlog.publisher_position = Some(position.value),
+                SENDER_POSITION_TYPE_ID => backlog.sender_position = Some(position.value),
+                SENDER_LIMIT_TYPE_ID => backlog.sender_limit = Some(position.value),
+                RECEIVER_HWM_TYPE_ID => backlog.receiver_hwm = Some(position.value),
+                RECEIVER_POSITION_TYPE_ID => backlog.receiver_position = Some(position.value),
+                _ => {}
+            }
+        }
+
+        let consumed_from = registrations
+            .values()
+            .min_by_key(|backlog| backlog.receiver_position.is_none())
+            .map(|backlog| backlog.registration_id);
+        for subscriber in subscribers {
+            let registration_id = consumed_from.unwrap_or(subscriber.registration_id);
+            registrations
+                .entry(registration_id)
+                .or_insert_with(|| StreamBacklog::new(&stream, registration_id))
+                .subscribers
+                .push(SubscriberBacklog {
+                    registration_id: subscriber.registration_id,
+                    position: subscriber.value,
+                    lag: None,
+                });
+        }
+
+        backlogs.extend(registrations.into_values().map(|mut backlog| {
+            let consumed_from = backlog.receiver_position.or(backlog.publisher_position);
+            for subscriber in &mut backlog.subscribers {
+                subscriber.lag = consumed_from.map(|position| position - subscriber.position);
+            }
+            backlog
+        }));
+    }
+
+    backlogs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::counters::stream_counter_key;
+
+    fn counter(type_id: i32, registration_id: i64, value: i64) -> CounterEntry {
+        CounterEntry {
+            id: 0,
+            type_id,
+            value,
+            label: String::new(),
+            key: stream_counter_key(
+                registration_id,
+                5,
+                1001,
+                "aeron:udp?endpoint=localhost:20121",
+            ),
+        }
+    }
+
+    #[test]
+    fn computes_backlogs() {
+        let counters = vec![
+            counter(PUBLISHER_POSITION_TYPE_ID, 10, 4096),
+            counter(SENDER_POSITION_TYPE_ID, 10, 1024),
+            counter(SENDER_LIMIT_TYPE_ID, 10, 66560),
+            counter(RECEIVER_HWM_TYPE_ID, 20, 1024),
+            counter(RECEIVER_POSITION_TYPE_ID, 20, 512),
+            counter(SUBSCRIBER_POSITION_TYPE_ID, 30, 256),
+        ];
+
+        let backlogs = stream_backlogs(&counters);
+        assert_eq!(backlogs.len(), 2);
+        let publication = &backlogs[0];
+        assert_eq!(publication.registration_id, 10);
+        assert_eq!(publication.publisher_backlog(), Some(3072));
+        assert_eq!(publication.sender_window(), Some(65536));
+        assert!(publication.subscribers.is_empty());
+
+        let image = &backlogs[1];
+        assert_eq!(image.registration_id, 20);
+        assert_eq!(image.receiver_backlog(), Some(512));
+        assert_eq!(
+            image.subscribers,
+            vec![SubscriberBacklog {
+                registration_id: 30,
+                position: 256,
+                lag: Some(256),
+            }]
+        );
+    }
+
+    #[test]
+    fn separates_registrations_of_the_same_stream() {
+        let counters = vec![
+            counter(PUBLISHER_POSITION_TYPE_ID, 10, 4096),
+            counter(SENDER_POSITION_TYPE_ID, 10, 4096),
+            counter(PUBLISHER_POSITION_TYPE_ID, 11, 128),
+            counter(SENDER_POSITION_TYPE_ID, 11, 64),
+        ];
+
+        let backlogs = stream_backlogs(&counters);
+        let registrations: Vec<_> = backlogs
+            .iter()
+            .map(|stream| (stream.registration_id, stream.publisher_backlog()))
+            .collect();
+        assert_eq!(registrations, vec![(10, Some(0)), (11, Some(64))]);
+    }
+
+    #[test]
+    fn attaches_ipc_subscribers_to_the_publication() {
+        let counters = vec![
+            counter(PUBLISHER_POSITION_TYPE_ID, 10, 4096),
+            counter(SUBSCRIBER_POSITION_TYPE_ID, 30, 1024),
+        ];
+
+        let backlogs = stream_backlogs(&counters);
+        assert_eq!(backlogs.len(), 1);
+        assert_eq!(backlogs[0].subscribers[0].lag, Some(3072));
+    }
+}
@@ -0,0 +1,211 @@
+use aeron_rs_media_driver::client::{
+    Aeron, AeronClientContext, AeronPublication, AeronSubscription,
+};
+use aeron_rs_media_driver::codec::{Decode, Encode};
+use clap::Parser;
+use hdrhistogram::Histogram;
+use std::time::{Duration, Instant};
+
+/// Measures round-trip latency against `aeron_pong`, which echoes every message back.
+#[derive(Parser, Debug)]
+#[command(name = "aeron_ping")]
+struct Args {
+    /// Aeron directory of the driver, defaults to the client default
+    #[arg(long)]
+    dir: Option<String>,
+
+    /// Channel pings are sent on
+    #[arg(long, default_value = "aeron:udp?endpoint=localhost:20123")]
+    ping_channel: String,
+
+    /// Stream id pings are sent on
+    #[arg(long, default_value_t = 1002)]
+    ping_stream_id: i32,
+
+    /// Channel pongs are received on
+    #[arg(long, default_value = "aeron:udp?endpoint=localhost:20124")]
+    pong_channel: String,
+
+    /// Stream id pongs are received on
+    #[arg(long, default_value_t = 1003)]
+    pong_stream_id: i32,
+
+    /// Number of measured round trips
+    #[arg(long, default_value_t = 100_000)]
+    messages: u64,
+
+    /// Number of round trips sent before measuring, to warm up both sides
+    #[arg(long, default_value_t = 10_000)]
+    warmup_messages: u64,
+
+    /// Length of each message in bytes, at least 8 to carry the send timestamp
+    #[arg(long, default_value_t = 32, value_parser = clap::value_parser!(u64).range(8..))]
+    message_length: u64,
+
+    /// Pings sent per second, 0 sends the next ping as soon as the pong arrives
+    #[arg(long, default_value_t = 0)]
+    rate: u64,
+
+    /// How long to wait for the publication and subscription to connect, in milliseconds
+    #[arg(long, default_value_t = 10_000)]
+    connect_timeout_ms: u64,
+
+    /// How long to wait for each pong before counting the round trip as timed out, in
+    /// milliseconds
+    #[arg(long, default_value_t = 1_000)]
+    pong_timeout_ms: u64,
+}
+
+/// A ping carrying its send time in the first 8 bytes, padded to the message length.
+struct Ping {
+    timestamp_ns: u64,
+    length: usize,
+}
+
+impl Encode for Ping {
+    fn encoded_length(&self) -> usize {
+        self.length
+    }
+
+    fn encode(&self, buffer: &mut [u8]) {
+        self.timestamp_ns.encode(&mut buffer[..8]);
+    }
+}
+
+/// Sends `count` pings and returns how many pongs did not arrive within the pong timeout.
+fn run_round_trips(
+    publication: &AeronPublication,
+    subscription: &AeronSubscription,
+    args: &Args,
+    count: u64,
+    mut histogram: Option<&mut Histogram<u64>>,
+) -> Result<u64, Box<dyn std::error::Error>> {
+    let start = Instant::now();
+    let pong_timeout = Duration::from_millis(args.pong_timeout_ms);
+    let mut timed_out = 0;
+    let interval = (args.rate > 0).then(|| Duration::from_nanos(1_000_000_000 / args.rate));
+    let mut next_send = Instant::now();
+
+    for _ in 0..count {
+        if let Some(interval) = interval {
+            while Instant::now() < next_send {
+                std::hint::spin_loop();
+            }
+            next_send += interval;
+        }
+
+        let ping = Ping {
+            timestamp_ns: start.elapsed().as_nanos() as u64,
+            length: args.message_length as usize,
+        };
+        loop {
+            match publication.offer_msg(&ping) {
+                Ok(_) => break,
+                Err(error) if error.is_retryable() => std::hint::spin_loop(),
+                Err(error) => return Err(error.into()),
+            }
+        }
+
+        // Pongs of earlier pings that timed out are skipped
+        let deadline = Instant::now() + pong_timeout;
+        let mut received = false;
+        while !received {
+            if Instant::now() >= deadline {
+                timed_out += 1;
+                break;
+            }
+            subscription.poll(
+                |buffer| {
+                    if let Ok(timestamp_ns) = u64::decode(buffer) {
+                        if timestamp_ns != ping.timestamp_ns {
+                            return;
+                        }
+                        let rtt_ns =
+                            (start.elapsed().as_nanos() as u64).saturating_sub(timestamp_ns);
+                        if let Some(histogram) = histogram.as_deref_mut() {
+                            histogram.saturating_record(rtt_ns);
+                        }
+                        received = true;
+                    }
+                },
+                1,
+            )?;
+        }
+    }
+
+    Ok(timed_out)
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+
+    let context = AeronClientContext::new()?;
+    if let Some(dir) = &args.dir {
+        context.set_dir(dir)?;
+    }
+    let aeron = Aeron::new(&context)?;
+    aeron.start()?;
+
+    let connect_timeout = Duration::from_millis(args.connect_timeout_ms);
+    let publication =
+        aeron.add_publication(&args.ping_channel, args.ping_stream_id, connect_timeout)?;
+    let subscription =
+        aeron.add_subscription(&args.pong_channel, args.pong_stream_id, connect_timeout)?;
+
+    println!(
+        "Publishing ping on {} stream {}, awaiting pong on {} stream {}",
+        args.ping_channel, args.ping_stream_id, args.pong_channel, args.pong_stream_id
+    );
+    let deadline = Instant::now() + connect_timeout;
+    while !publication.is_connected() || !subscription.is_connected() {
+        if Instant::now() >= deadline {
+            return Err("timed out waiting for aeron_pong to connect".into());
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    println!("Warming up with {} messages", args.warmup_messages);
+    let timed_out = run_round_trips(
+        &publication,
+        &subscription,
+        &args,
+        args.warmup_messages,
+        None,
+    )?;
+    if timed_out > 0 {
+        println!("{timed_out} warmup pongs timed out");
+    }
+
+    println!(
+        "Measuring {} messages of {} bytes",
+        args.messages, args.message_length
+    );
+    let mut histogram = Histogram::<u64>::new_with_bounds(1, 60_000_000_000, 3)?;
+    let timed_out = run_round_trips(
+        &publication,
+        &subscription,
+        &args,
+        args.messages,
+        Some(&mut histogram),
+    )?;
+
+    println!("Round trip latency in microseconds:");
+    for percentile in [50.0, 90.0, 99.0, 99.9, 99.99, 100.0] {
+        println!(
+            "{:>8}%: {:>10.3}",
+            percentile,
+            histogram.value_at_percentile(percentile) as f64 / 1000.0
+        );
+    }
+    println!(
+        "    mean: {:>10.3} over {} samples",
+        histogram.mean() / 1000.0,
+        histogram.len()
+    );
+    println!(
+        "timed out: {timed_out} pongs not received within {} ms",
+        args.pong_timeout_ms
+    );
+
+    Ok(())
+}
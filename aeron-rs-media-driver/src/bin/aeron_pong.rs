@@ -0,0 +1,85 @@
+use aeron_rs_media_driver::client::{Aeron, AeronClientContext};
+use clap::Parser;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Echoes every message received from `aeron_ping` back on the pong channel.
+#[derive(Parser, Debug)]
+#[command(name = "aeron_pong")]
+struct Args {
+    /// Aeron directory of the driver, defaults to the client default
+    #[arg(long)]
+    dir: Option<String>,
+
+    /// Channel pings are received on
+    #[arg(long, default_value = "aeron:udp?endpoint=localhost:20123")]
+    ping_channel: String,
+
+    /// Stream id pings are received on
+    #[arg(long, default_value_t = 1002)]
+    ping_stream_id: i32,
+
+    /// Channel pongs are sent on
+    #[arg(long, default_value = "aeron:udp?endpoint=localhost:20124")]
+    pong_channel: String,
+
+    /// Stream id pongs are sent on
+    #[arg(long, default_value_t = 1003)]
+    pong_stream_id: i32,
+
+    /// How long to wait for the publication and subscription to be added, in milliseconds
+    #[arg(long, default_value_t = 10_000)]
+    connect_timeout_ms: u64,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+
+    // Flag to indicate when the application should stop (set on Ctrl+C)
+    let running = Arc::new(AtomicBool::new(true));
+    let running_clone = Arc::clone(&running);
+    ctrlc::set_handler(move || {
+        running_clone.store(false, Ordering::SeqCst);
+    })?;
+
+    let context = AeronClientContext::new()?;
+    if let Some(dir) = &args.dir {
+        context.set_dir(dir)?;
+    }
+    let aeron = Aeron::new(&context)?;
+    aeron.start()?;
+
+    let connect_timeout = Duration::from_millis(args.connect_timeout_ms);
+    let subscription =
+        aeron.add_subscription(&args.ping_channel, args.ping_stream_id, connect_timeout)?;
+    let publication =
+        aeron.add_publication(&args.pong_channel, args.pong_stream_id, connect_timeout)?;
+
+    println!(
+        "Echoing {} stream {} to {} stream {}. Press Ctrl+C to stop.",
+        args.ping_channel, args.ping_stream_id, args.pong_channel, args.pong_stream_id
+    );
+
+    while running.load(Ordering::Acquire) {
+        let mut result = Ok(());
+        subscription.poll(
+            |buffer| loop {
+                match publication.offer(buffer) {
+                    Ok(_) => break,
+                    Err(error) if error.is_retryable() && running.load(Ordering::Acquire) => {
+                        std::hint::spin_loop()
+                    }
+                    Err(error) => {
+                        result = Err(error);
+                        break;
+                    }
+                }
+            },
+            10,
+        )?;
+        result?;
+    }
+
+    Ok(())
+}
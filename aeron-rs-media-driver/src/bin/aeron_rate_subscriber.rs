@@ -0,0 +1,71 @@
+use aeron_rs_media_driver::client::{Aeron, AeronClientContext};
+use aeron_rs_media_driver::rate_reporter::RateReporter;
+use clap::Parser;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Consumes messages as fast as possible and reports the achieved rate.
+#[derive(Parser, Debug)]
+#[command(name = "aeron_rate_subscriber")]
+struct Args {
+    /// Aeron directory of the driver, defaults to the client default
+    #[arg(long)]
+    dir: Option<String>,
+
+    /// Channel to subscribe to
+    #[arg(long, default_value = "aeron:udp?endpoint=localhost:20121")]
+    channel: String,
+
+    /// Stream id to subscribe to
+    #[arg(long, default_value_t = 1001)]
+    stream_id: i32,
+
+    /// Maximum fragments read per poll
+    #[arg(long, default_value_t = 256)]
+    fragment_limit: usize,
+
+    /// How long to wait for the subscription to be added, in milliseconds
+    #[arg(long, default_value_t = 10_000)]
+    connect_timeout_ms: u64,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+
+    // Flag to indicate when the application should stop (set on Ctrl+C)
+    let running = Arc::new(AtomicBool::new(true));
+    let running_clone = Arc::clone(&running);
+    ctrlc::set_handler(move || {
+        running_clone.store(false, Ordering::SeqCst);
+    })?;
+
+    let context = AeronClientContext::new()?;
+    if let Some(dir) = &args.dir {
+        context.set_dir(dir)?;
+    }
+    let aeron = Aeron::new(&context)?;
+    aeron.start()?;
+
+    let subscription = aeron.add_subscription(
+        &args.channel,
+        args.stream_id,
+        Duration::from_millis(args.connect_timeout_ms),
+    )?;
+    println!(
+        "Subscribed to {} stream {}. Press Ctrl+C to stop.",
+        args.channel, args.stream_id
+    );
+
+    let mut reporter = RateReporter::new(Duration::from_secs(1));
+    while running.load(Ordering::Acquire) {
+        subscription.poll(
+            |buffer| reporter.on_message(buffer.len()),
+            args.fragment_limit,
+        )?;
+        reporter.maybe_report();
+    }
+    reporter.finish();
+
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use aeron_rs_media_driver::cnc::CncFile;
+use aeron_rs_media_driver::counters::{read_counters, CounterFilter};
+use clap::Parser;
+use regex::Regex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Prints the counters of a running media driver.
+#[derive(Parser, Debug)]
+#[command(name = "aeron_stat")]
+struct Args {
+    /// Aeron directory of the driver, defaults to the client default
+    #[arg(long)]
+    dir: Option<String>,
+
+    /// Only show counters with this type id
+    #[arg(long)]
+    type_id: Option<i32>,
+
+    /// Only show counters whose label matches this regex
+    #[arg(long)]
+    label: Option<Regex>,
+
+    /// Only show stream counters for this session id
+    #[arg(long)]
+    session_id: Option<i32>,
+
+    /// Only show stream counters for this stream id
+    #[arg(long)]
+    stream_id: Option<i32>,
+
+    /// Only show counters whose channel contains this string
+    #[arg(long)]
+    channel: Option<String>,
+
+    /// Refresh interval in milliseconds
+    #[arg(long, default_value_t = 1000)]
+    interval_ms: u64,
+
+    /// How long to wait for the CnC file to become available, in milliseconds
+    #[arg(long, default_value_t = 5000)]
+    timeout_ms: u64,
+
+    /// Print the counters once and exit
+    #[arg(long)]
+    once: bool,
+}
+
+fn print_counters(cnc: &CncFile, filter: &CounterFilter) -> Result<(), Box<dyn std::error::Error>> {
+    let constants = cnc.constants()?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?;
+
+    println!(
+        "{} - Aeron Stat (CnC v{}), pid {}, heartbeat age {}ms",
+        now.as_millis(),
+        constants.version(),
+        constants.pid,
+        cnc.driver_heartbeat_age().as_millis()
+    );
+    println!("======================================================================");
+
+    for counter in read_counters(cnc)
+        .iter()
+        .filter(|counter| filter.matches(counter))
+    {
+        println!("{:3}: {:20} - {}", counter.id, counter.value, counter.label);
+    }
+
+    Ok(())
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+
+    let dir = match args.dir {
+        Some(dir) => dir,
+        None => CncFile::default_dir()?,
+    };
+    let cnc = CncFile::open(&dir, Duration::from_millis(args.timeout_ms))?;
+    let filter = CounterFilter {
+        type_id: args.type_id,
+        label: args.label,
+        session_id: args.session_id,
+        stream_id: args.stream_id,
+        channel: args.channel,
+    };
+
+    if args.once {
+        return print_counters(&cnc, &filter);
+    }
+
+    // Flag to indicate when the application should stop (set on Ctrl+C)
+    let running = Arc::new(AtomicBool::new(true));
+    let running_clone = Arc::clone(&running);
+    ctrlc::set_handler(move || {
+        running_clone.store(false, Ordering::SeqCst);
+    })?;
+
+    while running.load(Ordering::Acquire) {
+        // Clear the terminal and move the cursor home before redrawing
+        print!("\x1b[2J\x1b[H");
+        print_counters(&cnc, &filter)?;
+        std::thread::sleep(Duration::from_millis(args.interval_ms));
+    }
+
+    Ok(())
+}
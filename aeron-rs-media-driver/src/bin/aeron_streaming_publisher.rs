@@ -0,0 +1,90 @@
+use aeron_rs_media_driver::client::{Aeron, AeronClientContext, PublicationError};
+use aeron_rs_media_driver::rate_reporter::RateReporter;
+use clap::Parser;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Publishes fixed-size messages as fast as possible and reports the achieved rate.
+#[derive(Parser, Debug)]
+#[command(name = "aeron_streaming_publisher")]
+struct Args {
+    /// Aeron directory of the driver, defaults to the client default
+    #[arg(long)]
+    dir: Option<String>,
+
+    /// Channel to publish on
+    #[arg(long, default_value = "aeron:udp?endpoint=localhost:20121")]
+    channel: String,
+
+    /// Stream id to publish on
+    #[arg(long, default_value_t = 1001)]
+    stream_id: i32,
+
+    /// Length of each message in bytes
+    #[arg(long, default_value_t = 32)]
+    message_length: usize,
+
+    /// Number of messages to publish, 0 publishes until Ctrl+C
+    #[arg(long, default_value_t = 10_000_000)]
+    messages: u64,
+
+    /// How long to wait for the publication to be added, in milliseconds
+    #[arg(long, default_value_t = 10_000)]
+    connect_timeout_ms: u64,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+
+    // Flag to indicate when the application should stop (set on Ctrl+C)
+    let running = Arc::new(AtomicBool::new(true));
+    let running_clone = Arc::clone(&running);
+    ctrlc::set_handler(move || {
+        running_clone.store(false, Ordering::SeqCst);
+    })?;
+
+    let context = AeronClientContext::new()?;
+    if let Some(dir) = &args.dir {
+        context.set_dir(dir)?;
+    }
+    let aeron = Aeron::new(&context)?;
+    aeron.start()?;
+
+    let publication = aeron.add_publication(
+        &args.channel,
+        args.stream_id,
+        Duration::from_millis(args.connect_timeout_ms),
+    )?;
+    println!(
+        "Streaming {} byte messages to {} stream {}",
+        args.message_length, args.channel, args.stream_id
+    );
+
+    let payload = vec![0u8; args.message_length];
+    let mut reporter = RateReporter::new(Duration::from_secs(1));
+    let start = Instant::now();
+
+    while running.load(Ordering::Acquire)
+        && (args.messages == 0 || reporter.total_messages() < args.messages)
+    {
+        match publication.offer_msg(&payload[..]) {
+            Ok(_) => reporter.on_message(args.message_length),
+            Err(PublicationError::BackPressured | PublicationError::AdminAction) => {
+                reporter.on_back_pressure()
+            }
+            Err(PublicationError::NotConnected) => std::thread::yield_now(),
+            Err(error) => return Err(error.into()),
+        }
+        reporter.maybe_report();
+    }
+    reporter.finish();
+
+    println!(
+        "Published {} messages in {:.3}s",
+        reporter.total_messages(),
+        start.elapsed().as_secs_f64()
+    );
+
+    Ok(())
+}
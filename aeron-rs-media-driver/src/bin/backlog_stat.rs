@@ -0,0 +1,149 @@
+use aeron_rs_media_driver::backlog::{stream_backlogs, StreamBacklog};
+use aeron_rs_media_driver::cnc::CncFile;
+use aeron_rs_media_driver::counters::read_counters;
+use clap::Parser;
+use serde::Serialize;
+use std::time::Duration;
+
+/// Prints the publisher, sender, receiver and subscriber backlog of each publication and
+/// image.
+#[derive(Parser, Debug)]
+#[command(name = "backlog_stat")]
+struct Args {
+    /// Aeron directory of the driver, defaults to the client default
+    #[arg(long)]
+    dir: Option<String>,
+
+    /// Print the backlogs as a JSON array
+    #[arg(long)]
+    json: bool,
+
+    /// How long to wait for the CnC file to become available, in milliseconds
+    #[arg(long, default_value_t = 5000)]
+    timeout_ms: u64,
+}
+
+/// A stream's positions alongside the backlogs computed from them.
+#[derive(Serialize)]
+struct StreamBacklogReport<'a> {
+    #[serde(flatten)]
+    stream: &'a StreamBacklog,
+    publisher_backlog: Option<i64>,
+    sender_window: Option<i64>,
+    receiver_backlog: Option<i64>,
+}
+
+fn format_optional(value: Option<i64>) -> String {
+    value.map_or_else(|| "-".to_string(), |value| value.to_string())
+}
+
+/// Prints `rows` under `headers` with every column padded to its widest cell.
+fn print_table(headers: &[&str], rows: &[Vec<String>]) {
+    let mut widths: Vec<_> = headers.iter().map(|header| header.len()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let print_row = |cells: &mut dyn Iterator<Item = &str>| {
+        let line: Vec<_> = cells
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect();
+        println!("{}", line.join("  ").trim_end());
+    };
+    print_row(&mut headers.iter().copied());
+    for row in rows {
+        print_row(&mut row.iter().map(String::as_str));
+    }
+}
+
+fn print_backlogs(backlogs: &[StreamBacklog]) {
+    let streams: Vec<_> = backlogs
+        .iter()
+        .map(|stream| {
+            vec![
+                stream.registration_id.to_string(),
+                stream.session_id.to_string(),
+                stream.stream_id.to_string(),
+                format_optional(stream.publisher_position),
+                format_optional(stream.publisher_backlog()),
+                format_optional(stream.sender_position),
+                format_optional(stream.sender_limit),
+                format_optional(stream.sender_window()),
+                format_optional(stream.receiver_hwm),
+                format_optional(stream.receiver_position),
+                format_optional(stream.receiver_backlog()),
+                stream.channel.clone(),
+            ]
+        })
+        .collect();
+    print_table(
+        &[
+            "REGISTRATION_ID",
+            "SESSION_ID",
+            "STREAM_ID",
+            "PUB_POS",
+            "PUB_BACKLOG",
+            "SND_POS",
+            "SND_LMT",
+            "SND_WINDOW",
+            "RCV_HWM",
+            "RCV_POS",
+            "RCV_BACKLOG",
+            "CHANNEL",
+        ],
+        &streams,
+    );
+
+    let subscribers: Vec<_> = backlogs
+        .iter()
+        .flat_map(|stream| {
+            stream.subscribers.iter().map(|subscriber| {
+                vec![
+                    subscriber.registration_id.to_string(),
+                    stream.registration_id.to_string(),
+                    subscriber.position.to_string(),
+                    format_optional(subscriber.lag),
+                ]
+            })
+        })
+        .collect();
+    if !subscribers.is_empty() {
+        println!();
+        print_table(
+            &["SUBSCRIPTION_ID", "CONSUMES_FROM", "SUB_POS", "LAG"],
+            &subscribers,
+        );
+    }
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+
+    let dir = match args.dir {
+        Some(dir) => dir,
+        None => CncFile::default_dir()?,
+    };
+    let cnc = CncFile::open(&dir, Duration::from_millis(args.timeout_ms))?;
+    let backlogs = stream_backlogs(&read_counters(&cnc));
+
+    if args.json {
+        let streams: Vec<_> = backlogs
+            .iter()
+            .map(|stream| StreamBacklogReport {
+                stream,
+                publisher_backlog: stream.publisher_backlog(),
+                sender_window: stream.sender_window(),
+                receiver_backlog: stream.receiver_backlog(),
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&streams)?);
+        return Ok(());
+    }
+
+    print_backlogs(&backlogs);
+
+    Ok(())
+}
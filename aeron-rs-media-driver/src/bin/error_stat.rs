@@ -0,0 +1,86 @@
+use aeron_rs_media_driver::cnc::CncFile;
+use aeron_rs_media_driver::format_epoch_ms;
+use clap::Parser;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Prints the distinct error log of a running media driver.
+#[derive(Parser, Debug)]
+#[command(name = "error_stat")]
+struct Args {
+    /// Aeron directory of the driver, defaults to the client default
+    #[arg(long)]
+    dir: Option<String>,
+
+    /// Only show errors last observed since this point, either a duration ago such as
+    /// `30s`, `15m`, `2h` or `1d`, or an epoch millisecond timestamp
+    #[arg(long, value_parser = parse_since)]
+    since: Option<i64>,
+
+    /// Print the entries as a JSON array
+    #[arg(long)]
+    json: bool,
+
+    /// How long to wait for the CnC file to become available, in milliseconds
+    #[arg(long, default_value_t = 5000)]
+    timeout_ms: u64,
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
+
+/// Resolves `--since` to epoch milliseconds.
+fn parse_since(value: &str) -> Result<i64, String> {
+    let unit_ms = match value.chars().last() {
+        Some('s') => 1_000,
+        Some('m') => 60_000,
+        Some('h') => 3_600_000,
+        Some('d') => 86_400_000,
+        _ => {
+            return value
+                .parse::<i64>()
+                .map_err(|error| format!("invalid timestamp {value}: {error}"))
+        }
+    };
+    let amount = value[..value.len() - 1]
+        .parse::<i64>()
+        .map_err(|error| format!("invalid duration {value}: {error}"))?;
+    amount
+        .checked_mul(unit_ms)
+        .and_then(|ago_ms| now_ms().checked_sub(ago_ms))
+        .ok_or_else(|| format!("duration {value} is out of range"))
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+
+    let dir = match args.dir {
+        Some(dir) => dir,
+        None => CncFile::default_dir()?,
+    };
+    let cnc = CncFile::open(&dir, Duration::from_millis(args.timeout_ms))?;
+    let entries = cnc.read_error_log(args.since.unwrap_or(0));
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+
+    for entry in &entries {
+        println!("***");
+        println!(
+            "{} observations from {} to {} for:",
+            entry.observation_count,
+            format_epoch_ms(entry.first_observation_timestamp_ms),
+            format_epoch_ms(entry.last_observation_timestamp_ms)
+        );
+        println!(" {}", entry.message);
+    }
+    println!();
+    println!("{} distinct errors observed.", entries.len());
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use aeron_rs_media_driver::cnc::CncFile;
+use aeron_rs_media_driver::format_epoch_ms;
+use aeron_rs_media_driver::loss_report::{diff_loss_reports, read_loss_report, LossReportEntry};
+use clap::Parser;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Prints the loss report of a running media driver.
+#[derive(Parser, Debug)]
+#[command(name = "loss_stat")]
+struct Args {
+    /// Aeron directory of the driver, defaults to the client default
+    #[arg(long)]
+    dir: Option<String>,
+
+    /// Only show loss observed since the snapshot saved in this file
+    #[arg(long)]
+    baseline: Option<PathBuf>,
+
+    /// Save the current report to this file for a later `--baseline`
+    #[arg(long)]
+    save: Option<PathBuf>,
+
+    /// Print the entries as a JSON array
+    #[arg(long)]
+    json: bool,
+
+    /// How long to wait for the CnC file to become available, in milliseconds
+    #[arg(long, default_value_t = 5000)]
+    timeout_ms: u64,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+
+    let dir = match args.dir {
+        Some(dir) => dir,
+        None => CncFile::default_dir()?,
+    };
+    let cnc = CncFile::open(&dir, Duration::from_millis(args.timeout_ms))?;
+    let current = read_loss_report(&cnc)?;
+
+    if let Some(path) = &args.save {
+        fs::write(path, serde_json::to_string_pretty(&current)?)?;
+    }
+
+    let entries = match &args.baseline {
+        Some(path) => {
+            let baseline: Vec<LossReportEntry> = serde_json::from_str(&fs::read_to_string(path)?)?;
+            diff_loss_reports(&baseline, &current)
+        }
+        None => current,
+    };
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+
+    println!(
+        "#OBSERVATION_COUNT, TOTAL_BYTES_LOST, FIRST_OBSERVATION, LAST_OBSERVATION, SESSION_ID, STREAM_ID, CHANNEL, SOURCE"
+    );
+    for entry in &entries {
+        println!(
+            "{}, {}, {}, {}, {}, {}, {}, {}",
+            entry.observation_count,
+            entry.total_bytes_lost,
+            format_epoch_ms(entry.first_observation_timestamp_ms),
+            format_epoch_ms(entry.last_observation_timestamp_ms),
+            entry.session_id,
+            entry.stream_id,
+            entry.channel,
+            entry.source
+        );
+    }
+
+    Ok(())
+}
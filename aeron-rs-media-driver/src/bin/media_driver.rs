@@ -1,8 +1,69 @@
+use aeron_rs_media_driver::agent_thread::AgentAffinity;
+use aeron_rs_media_driver::cnc::{check_driver, CncFile};
+#[cfg(feature = "driver-agent")]
+use aeron_rs_media_driver::driver_agent::EventLogForwarder;
+use aeron_rs_media_driver::duty_cycle::{read_duty_cycle_stats, StallMonitor};
 use aeron_rs_media_driver::media_driver::{AeronContext, AeronDriver};
+#[cfg(feature = "metrics")]
+use aeron_rs_media_driver::metrics::MetricsServer;
+use clap::Parser;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const STALL_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Runs an embedded Aeron media driver until Ctrl+C is pressed.
+#[derive(Parser, Debug)]
+#[command(name = "media_driver")]
+struct Args {
+    /// Check whether a driver is running in DIR, or the default aeron dir, and exit with
+    /// status 0 if it is alive or 1 otherwise
+    #[arg(long, value_name = "DIR", num_args = 0..=1)]
+    check: Option<Option<String>>,
+
+    /// Heartbeat age after which `--check` treats the driver as dead, in milliseconds
+    #[arg(long, default_value_t = 10_000)]
+    check_timeout_ms: u64,
+
+    /// Log each time a driver agent's duty cycle exceeds its threshold, checked once per
+    /// second
+    #[arg(long)]
+    log_stalls: bool,
+
+    /// Pin and name the driver's agent threads, e.g. `conductor=1,sender=2,receiver=3-4`
+    #[arg(long, value_name = "MAP")]
+    agent_affinity: Option<String>,
+
+    /// Duty cycle threshold of the conductor, sender and receiver agents, in nanoseconds
+    #[arg(long, value_name = "NS")]
+    cycle_threshold_ns: Option<u64>,
+
+    /// Serve the driver counters as Prometheus metrics on this address, e.g. `0.0.0.0:9090`
+    #[cfg(feature = "metrics")]
+    #[arg(long)]
+    metrics_addr: Option<String>,
+
+    /// Log driver agent events, e.g. `admin` or `FRAME_IN,FRAME_OUT`, through `tracing`;
+    /// filter the output with `RUST_LOG`
+    #[cfg(feature = "driver-agent")]
+    #[arg(long, value_name = "EVENTS")]
+    event_log: Option<String>,
+}
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+
+    if let Some(dir) = &args.check {
+        let dir = match dir {
+            Some(dir) => dir.clone(),
+            None => AeronContext::new()?.get_dir(),
+        };
+        let status = check_driver(&dir, Duration::from_millis(args.check_timeout_ms));
+        println!("{dir}: {status}");
+        std::process::exit(if status.is_active() { 0 } else { 1 });
+    }
+
     // Flag to indicate when the application should stop (set on Ctrl+C)
     let running = Arc::new(AtomicBool::new(true));
     let running_clone = Arc::clone(&running);
@@ -14,6 +75,28 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Create Aeron context
     let aeron_context = AeronContext::new()?;
+    #[cfg(feature = "driver-agent")]
+    let _event_log_forwarder = match &args.event_log {
+        Some(events) => {
+            tracing_subscriber::fmt()
+                .with_env_filter(
+                    tracing_subscriber::EnvFilter::try_from_default_env()
+                        .unwrap_or_else(|_| "aeron_driver_agent=trace".into()),
+                )
+                .init();
+            aeron_context.set_event_log(events)?;
+            Some(EventLogForwarder::start()?)
+        }
+        None => None,
+    };
+    if let Some(map) = &args.agent_affinity {
+        aeron_context.set_agent_affinity(AgentAffinity::parse(map)?)?;
+    }
+    if let Some(threshold_ns) = args.cycle_threshold_ns {
+        aeron_context.set_conductor_cycle_threshold_ns(threshold_ns)?;
+        aeron_context.set_sender_cycle_threshold_ns(threshold_ns)?;
+        aeron_context.set_receiver_cycle_threshold_ns(threshold_ns)?;
+    }
     aeron_context.print_config()?;
 
     // Create Aeron driver
@@ -23,9 +106,38 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     aeron_driver.start()?;
     println!("Aeron media driver started successfully. Press Ctrl+C to stop.");
 
+    #[cfg(feature = "metrics")]
+    let _metrics_server = match &args.metrics_addr {
+        Some(addr) => {
+            println!("Serving driver metrics on http://{addr}/metrics");
+            Some(MetricsServer::start(addr, &aeron_context.get_dir())?)
+        }
+        None => None,
+    };
+
+    let aeron_dir = aeron_context.get_dir();
+    let mut stall_monitor = StallMonitor::new();
+    let mut cnc: Option<CncFile> = None;
+    let mut next_stall_check = Instant::now();
+
     // Poll for work until Ctrl+C is pressed
     while running.load(Ordering::Acquire) {
         aeron_driver.do_work();
+
+        if args.log_stalls && Instant::now() >= next_stall_check {
+            next_stall_check += STALL_CHECK_INTERVAL;
+            if cnc.is_none() {
+                cnc = CncFile::open(&aeron_dir, Duration::ZERO).ok();
+            }
+            if let Some(cnc) = &cnc {
+                for stall in stall_monitor.check(&read_duty_cycle_stats(cnc)) {
+                    eprintln!(
+                        "{} duty cycle exceeded its threshold {} time(s), max cycle time {} ns",
+                        stall.role, stall.count, stall.max_cycle_time_ns
+                    );
+                }
+            }
+        }
     }
 
     println!("Received signal to stop the media driver.");
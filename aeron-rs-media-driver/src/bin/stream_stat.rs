@@ -0,0 +1,94 @@
+use aeron_rs_media_driver::cnc::CncFile;
+use aeron_rs_media_driver::counters::{read_counters, CounterFilter};
+use aeron_rs_media_driver::streams::group_by_stream;
+use clap::Parser;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Prints the positions of each stream of a running media driver side by side.
+#[derive(Parser, Debug)]
+#[command(name = "stream_stat")]
+struct Args {
+    /// Aeron directory of the driver, defaults to the client default
+    #[arg(long)]
+    dir: Option<String>,
+
+    /// Only show streams with this stream id
+    #[arg(long)]
+    stream_id: Option<i32>,
+
+    /// Only show streams whose channel contains this string
+    #[arg(long)]
+    channel: Option<String>,
+
+    /// Refresh interval in milliseconds
+    #[arg(long, default_value_t = 1000)]
+    interval_ms: u64,
+
+    /// How long to wait for the CnC file to become available, in milliseconds
+    #[arg(long, default_value_t = 5000)]
+    timeout_ms: u64,
+
+    /// Print the streams once and exit
+    #[arg(long)]
+    once: bool,
+}
+
+fn print_streams(cnc: &CncFile, filter: &CounterFilter) {
+    let counters: Vec<_> = read_counters(cnc)
+        .into_iter()
+        .filter(|counter| filter.matches(counter))
+        .collect();
+
+    for stream in group_by_stream(&counters) {
+        let positions: Vec<_> = stream
+            .positions
+            .iter()
+            .map(|position| format!("{}:{}", position.name, position.value))
+            .collect();
+        println!(
+            "sessionId={} streamId={} channel={} : {}",
+            stream.session_id,
+            stream.stream_id,
+            stream.channel,
+            positions.join(" ")
+        );
+    }
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+
+    let dir = match args.dir {
+        Some(dir) => dir,
+        None => CncFile::default_dir()?,
+    };
+    let cnc = CncFile::open(&dir, Duration::from_millis(args.timeout_ms))?;
+    let filter = CounterFilter {
+        stream_id: args.stream_id,
+        channel: args.channel,
+        ..Default::default()
+    };
+
+    if args.once {
+        print_streams(&cnc, &filter);
+        return Ok(());
+    }
+
+    // Flag to indicate when the application should stop (set on Ctrl+C)
+    let running = Arc::new(AtomicBool::new(true));
+    let running_clone = Arc::clone(&running);
+    ctrlc::set_handler(move || {
+        running_clone.store(false, Ordering::SeqCst);
+    })?;
+
+    while running.load(Ordering::Acquire) {
+        // Clear the terminal and move the cursor home before redrawing
+        print!("\x1b[2J\x1b[H");
+        print_streams(&cnc, &filter);
+        std::thread::sleep(Duration::from_millis(args.interval_ms));
+    }
+
+    Ok(())
+}
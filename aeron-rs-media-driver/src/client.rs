@@ -0,0 +1,600 @@
+use libaeron_sys as aeron;
+
+use std::any::Any;
+use std::cell::Cell;
+use std::ffi::{c_void, CStr, CString};
+use std::fmt;
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use std::{ptr, slice};
+
+use crate::codec::{DecodeOwned, Encode};
+use crate::common;
+use crate::common::ManagedCResource;
+
+/// Returns the last error message recorded by the Aeron client library on this thread.
+pub(crate) fn aeron_errmsg() -> String {
+    unsafe { CStr::from_ptr(aeron::aeron_errmsg()) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+pub struct AeronClientContext {
+    resource: ManagedCResource<aeron::aeron_context_t>,
+}
+
+impl AeronClientContext {
+    pub fn new() -> common::Result<Self, Box<dyn std::error::Error>> {
+        let resource = ManagedCResource::new(
+            |ctx| unsafe { aeron::aeron_context_init(ctx) },
+            |ctx| unsafe { aeron::aeron_context_close(ctx) },
+        )
+        .map_err(|error_code| {
+            format!("failed to initialise aeron client context error code {error_code}")
+        })?;
+
+        Ok(Self { resource })
+    }
+
+    pub fn set_dir(&self, dir: &str) -> common::Result<()> {
+        let dir = CString::new(dir)?;
+        let result = unsafe { aeron::aeron_context_set_dir(self.resource.get(), dir.as_ptr()) };
+        if result < 0 {
+            return Err(format!("failed to set aeron dir error code {result}").into());
+        }
+        Ok(())
+    }
+
+    pub fn get_dir(&self) -> String {
+        unsafe { CStr::from_ptr(aeron::aeron_context_get_dir(self.resource.get())) }
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+pub struct Aeron {
+    resource: Rc<ManagedCResource<aeron::aeron_t>>,
+}
+
+impl Aeron {
+    pub fn new(context: &AeronClientContext) -> common::Result<Self, Box<dyn std::error::Error>> {
+        let resource = ManagedCResource::new(
+            |client| unsafe { aeron::aeron_init(client, context.resource.get()) },
+            |client| unsafe { aeron::aeron_close(client) },
+        )
+        .map_err(|error_code| {
+            format!(
+                "failed to initialise aeron client error code {error_code}: {}",
+                aeron_errmsg()
+            )
+        })?;
+
+        Ok(Self {
+            resource: Rc::new(resource),
+        })
+    }
+
+    pub fn start(&self) -> common::Result<(), Box<dyn std::error::Error>> {
+        let result = unsafe { aeron::aeron_start(self.resource.get()) };
+        if result < 0 {
+            return Err(format!(
+                "failed to start aeron client error code {result}: {}",
+                aeron_errmsg()
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    pub fn add_publication(
+        &self,
+        channel: &str,
+        stream_id: i32,
+        timeout: Duration,
+    ) -> common::Result<AeronPublication> {
+        let channel = CString::new(channel)?;
+        let client = self.resource.get();
+        let resource = ManagedCResource::new(
+            |publication| unsafe {
+                let mut async_add = ptr::null_mut();
+                let result = aeron::aeron_async_add_publication(
+                    &mut async_add,
+                    client,
+                    channel.as_ptr(),
+                    stream_id,
+                );
+                if result < 0 {
+                    return result;
+                }
+                poll_until_complete(timeout, || {
+                    aeron::aeron_async_add_publication_poll(publication, async_add)
+                })
+            },
+            |publication| unsafe {
+                aeron::aeron_publication_close(publication, None, ptr::null_mut())
+            },
+        )
+        .map_err(|error_code| {
+            format!(
+                "failed to add publication {channel:?} stream {stream_id} error code {error_code}: {}",
+                aeron_errmsg()
+            )
+        })?;
+
+        let mut constants: aeron::aeron_publication_constants_t = unsafe { std::mem::zeroed() };
+        let result = unsafe { aeron::aeron_publication_constants(resource.get(), &mut constants) };
+        if result < 0 {
+            return Err(format!("failed to read publication constants error code {result}").into());
+        }
+
+        Ok(AeronPublication {
+            resource,
+            max_payload_length: constants.max_payload_length,
+            _client: Rc::clone(&self.resource),
+        })
+    }
+
+    pub fn add_subscription(
+        &self,
+        channel: &str,
+        stream_id: i32,
+        timeout: Duration,
+    ) -> common::Result<AeronSubscription> {
+        let channel = CString::new(channel)?;
+        let client = self.resource.get();
+        let resource = ManagedCResource::new(
+            |subscription| unsafe {
+                let mut async_add = ptr::null_mut();
+                let result = aeron::aeron_async_add_subscription(
+                    &mut async_add,
+                    client,
+                    channel.as_ptr(),
+                    stream_id,
+                    None,
+                    ptr::null_mut(),
+                    None,
+                    ptr::null_mut(),
+                );
+                if result < 0 {
+                    return result;
+                }
+                poll_until_complete(timeout, || {
+                    aeron::aeron_async_add_subscription_poll(subscription, async_add)
+                })
+            },
+            |subscription| unsafe {
+                aeron::aeron_subscription_close(subscription, None, ptr::null_mut())
+            },
+        )
+        .map_err(|error_code| {
+            format!(
+                "failed to add subscription {channel:?} stream {stream_id} error code {error_code}: {}",
+                aeron_errmsg()
+            )
+        })?;
+
+        // The sink is boxed so the address handed to the assembler stays stable.
+        let sink = Box::new(FragmentSink::default());
+        let sink_ptr = &*sink as *const FragmentSink as *mut c_void;
+        let assembler = ManagedCResource::new(
+            |assembler| unsafe {
+                aeron::aeron_fragment_assembler_create(
+                    assembler,
+                    Some(on_assembled_fragment),
+                    sink_ptr,
+                )
+            },
+            |assembler| unsafe { aeron::aeron_fragment_assembler_delete(assembler) },
+        )
+        .map_err(|error_code| {
+            format!("failed to create fragment assembler error code {error_code}")
+        })?;
+
+        Ok(AeronSubscription {
+            assembler,
+            sink,
+            resource,
+            _client: Rc::clone(&self.resource),
+        })
+    }
+}
+
+/// Polls an async add operation until it completes, fails or `timeout` elapses.
+fn poll_until_complete(timeout: Duration, mut poll: impl FnMut() -> i32) -> i32 {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let result = poll();
+        if result != 0 {
+            return result;
+        }
+        if Instant::now() >= deadline {
+            return -1;
+        }
+        std::thread::yield_now();
+    }
+}
+
+/// Why an offer to a publication did not result in a new stream position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PublicationError {
+    NotConnected,
+    BackPressured,
+    AdminAction,
+    Closed,
+    MaxPositionExceeded,
+    Error(String),
+}
+
+impl PublicationError {
+    fn from_code(code: i64) -> Self {
+        match code {
+            code if code == aeron::AERON_PUBLICATION_NOT_CONNECTED as i64 => Self::NotConnected,
+            code if code == aeron::AERON_PUBLICATION_BACK_PRESSURED as i64 => Self::BackPressured,
+            code if code == aeron::AERON_PUBLICATION_ADMIN_ACTION as i64 => Self::AdminAction,
+            code if code == aeron::AERON_PUBLICATION_CLOSED as i64 => Self::Closed,
+            code if code == aeron::AERON_PUBLICATION_MAX_POSITION_EXCEEDED as i64 => {
+                Self::MaxPositionExceeded
+            }
+            code => Self::Error(format!("error code {code}: {}", aeron_errmsg())),
+        }
+    }
+
+    /// `true` when retrying the same offer later may succeed.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Self::NotConnected | Self::BackPressured | Self::AdminAction
+        )
+    }
+}
+
+impl fmt::Display for PublicationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotConnected => write!(f, "publication not connected"),
+            Self::BackPressured => write!(f, "publication back pressured"),
+            Self::AdminAction => write!(f, "publication admin action"),
+            Self::Closed => write!(f, "publication closed"),
+            Self::MaxPositionExceeded => write!(f, "publication max position exceeded"),
+            Self::Error(message) => write!(f, "publication error {message}"),
+        }
+    }
+}
+
+impl std::error::Error for PublicationError {}
+
+pub struct AeronPublication {
+    resource: ManagedCResource<aeron::aeron_publication_t>,
+    max_payload_length: usize,
+    _client: Rc<ManagedCResource<aeron::aeron_t>>,
+}
+
+impl AeronPublication {
+    pub fn is_connected(&self) -> bool {
+        unsafe { aeron::aeron_publication_is_connected(self.resource.get()) }
+    }
+
+    pub fn max_payload_length(&self) -> usize {
+        self.max_payload_length
+    }
+
+    pub fn offer(&self, buffer: &[u8]) -> Result<i64, PublicationError> {
+        let position = unsafe {
+            aeron::aeron_publication_offer(
+                self.resource.get(),
+                buffer.as_ptr(),
+                buffer.len(),
+                None,
+                ptr::null_mut(),
+            )
+        };
+        if position < 0 {
+            return Err(PublicationError::from_code(position));
+        }
+        Ok(position)
+    }
+
+    /// Claims `length` bytes in the term buffer, lets `fill` write the message in place and
+    /// commits it. `length` must not exceed the max payload length. If `fill` panics the
+    /// claim is aborted, so the term is not left blocked.
+    pub fn try_claim(
+        &self,
+        length: usize,
+        fill: impl FnOnce(&mut [u8]),
+    ) -> Result<i64, PublicationError> {
+        if length > self.max_payload_length {
+            return Err(PublicationError::Error(format!(
+                "claim length {length} exceeds max payload length {}",
+                self.max_payload_length
+            )));
+        }
+
+        let mut claim: aeron::aeron_buffer_claim_t = unsafe { std::mem::zeroed() };
+        let position =
+            unsafe { aeron::aeron_publication_try_claim(self.resource.get(), length, &mut claim) };
+        if position < 0 {
+            return Err(PublicationError::from_code(position));
+        }
+
+        let guard = ClaimAbortGuard(&mut claim);
+        fill(unsafe { slice::from_raw_parts_mut(guard.0.data, guard.0.length) });
+        std::mem::forget(guard);
+        let result = unsafe { aeron::aeron_buffer_claim_commit(&mut claim) };
+        if result < 0 {
+            return Err(PublicationError::Error(format!(
+                "failed to commit claim error code {result}: {}",
+                aeron_errmsg()
+            )));
+        }
+        Ok(position)
+    }
+
+    /// Encodes `msg` straight into the term buffer using try_claim when it fits in a single
+    /// frame, otherwise encodes into a scratch buffer and offers it as a fragmented message.
+    pub fn offer_msg<T: Encode + ?Sized>(&self, msg: &T) -> Result<i64, PublicationError> {
+        let length = msg.encoded_length();
+        if length > self.max_payload_length {
+            let mut buffer = vec![0u8; length];
+            msg.encode(&mut buffer);
+            return self.offer(&buffer);
+        }
+
+        self.try_claim(length, |buffer| msg.encode(buffer))
+    }
+}
+
+/// Aborts a claim when dropped, which only happens if the closure filling it panicked.
+struct ClaimAbortGuard<'a>(&'a mut aeron::aeron_buffer_claim_t);
+
+impl Drop for ClaimAbortGuard<'_> {
+    fn drop(&mut self) {
+        unsafe { aeron::aeron_buffer_claim_abort(self.0) };
+    }
+}
+
+/// What a controlled poll handler wants done with the fragment it was given.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlledPollAction {
+    /// Do not consume the fragment and stop polling; it is delivered again on the next poll.
+    Abort,
+    /// Consume the fragment and stop polling.
+    Break,
+    /// Consume the fragment and commit the position before continuing.
+    Commit,
+    /// Consume the fragment and continue polling.
+    Continue,
+}
+
+impl ControlledPollAction {
+    fn to_raw(self) -> aeron::aeron_controlled_fragment_handler_action_t {
+        match self {
+            ControlledPollAction::Abort => {
+                aeron::aeron_controlled_fragment_handler_action_en_AERON_ACTION_ABORT
+            }
+            ControlledPollAction::Break => {
+                aeron::aeron_controlled_fragment_handler_action_en_AERON_ACTION_BREAK
+            }
+            ControlledPollAction::Commit => {
+                aeron::aeron_controlled_fragment_handler_action_en_AERON_ACTION_COMMIT
+            }
+            ControlledPollAction::Continue => {
+                aeron::aeron_controlled_fragment_handler_action_en_AERON_ACTION_CONTINUE
+            }
+        }
+    }
+}
+
+/// A poll handler together with the panic it raised, if any. Panics must not unwind through
+/// the C poll loop, so they are caught in the callback and resumed once the poll returns.
+struct ControlledHandler<F> {
+    handler: F,
+    panic: Option<Box<dyn Any + Send>>,
+}
+
+unsafe extern "C" fn on_controlled_fragment<F: FnMut(&[u8]) -> ControlledPollAction>(
+    clientd: *mut c_void,
+    buffer: *const u8,
+    length: usize,
+    _header: *mut aeron::aeron_header_t,
+) -> aeron::aeron_controlled_fragment_handler_action_t {
+    let state = &mut *(clientd as *mut ControlledHandler<F>);
+    if state.panic.is_some() {
+        return ControlledPollAction::Abort.to_raw();
+    }
+    let buffer = slice::from_raw_parts(buffer, length);
+    match panic::catch_unwind(AssertUnwindSafe(|| (state.handler)(buffer))) {
+        Ok(action) => action.to_raw(),
+        Err(payload) => {
+            state.panic = Some(payload);
+            ControlledPollAction::Abort.to_raw()
+        }
+    }
+}
+
+type HandlerCall = unsafe fn(*mut c_void, &[u8]);
+
+/// Type-erased pointer to the handler of the poll currently in progress, and the panic it
+/// raised, if any.
+struct FragmentSink {
+    handler: Cell<*mut c_void>,
+    call: Cell<Option<HandlerCall>>,
+    panic: Cell<Option<Box<dyn Any + Send>>>,
+}
+
+impl Default for FragmentSink {
+    fn default() -> Self {
+        Self {
+            handler: Cell::new(ptr::null_mut()),
+            call: Cell::new(None),
+            panic: Cell::new(None),
+        }
+    }
+}
+
+unsafe fn call_handler<F: FnMut(&[u8])>(handler: *mut c_void, buffer: &[u8]) {
+    (*(handler as *mut F))(buffer)
+}
+
+unsafe extern "C" fn on_assembled_fragment(
+    clientd: *mut c_void,
+    buffer: *const u8,
+    length: usize,
+    _header: *mut aeron::aeron_header_t,
+) {
+    let sink = &*(clientd as *const FragmentSink);
+    let Some(call) = sink.call.get() else {
+        return;
+    };
+    let handler = sink.handler.get();
+    let buffer = slice::from_raw_parts(buffer, length);
+    if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| call(handler, buffer))) {
+        // Later fragments of this poll are dropped rather than handed to a panicked handler.
+        sink.call.set(None);
+        sink.panic.set(Some(payload));
+    }
+}
+
+pub struct AeronSubscription {
+    // Declared before `sink` so the assembler is deleted before the sink it points at.
+    assembler: ManagedCResource<aeron::aeron_fragment_assembler_t>,
+    sink: Box<FragmentSink>,
+    resource: ManagedCResource<aeron::aeron_subscription_t>,
+    _client: Rc<ManagedCResource<aeron::aeron_t>>,
+}
+
+impl AeronSubscription {
+    pub fn is_connected(&self) -> bool {
+        unsafe { aeron::aeron_subscription_is_connected(self.resource.get()) }
+    }
+
+    pub fn image_count(&self) -> i32 {
+        unsafe { aeron::aeron_subscription_image_count(self.resource.get()) }
+    }
+
+    /// The subscription's channel with a wildcard port, e.g. `endpoint=localhost:0`, replaced
+    /// by the port the driver bound. `None` until the driver has bound the endpoint.
+    pub fn try_resolve_channel_endpoint_port(&self) -> common::Result<Option<String>> {
+        let mut uri = vec![0u8; 1024];
+        loop {
+            let length = unsafe {
+                aeron::aeron_subscription_try_resolve_channel_endpoint_port(
+                    self.resource.get(),
+                    uri.as_mut_ptr() as *mut c_char,
+                    uri.len(),
+                )
+            };
+            if length < 0 {
+                return Err(format!(
+                    "failed to resolve subscription channel error code {length}: {}",
+                    aeron_errmsg()
+                )
+                .into());
+            }
+            if length == 0 {
+                return Ok(None);
+            }
+            let length = length as usize;
+            if length < uri.len() {
+                return Ok(Some(String::from_utf8_lossy(&uri[..length]).into_owned()));
+            }
+            // Truncated, so retry with room for the whole uri and its terminator
+            uri.resize(length + 1, 0);
+        }
+    }
+
+    /// Polls for messages, reassembling fragmented messages before handing them to `handler`.
+    ///
+    /// A panic in `handler` is resumed once the underlying poll has returned. Fragments the
+    /// poll reads after the panic are consumed without being handed to `handler`.
+    pub fn poll<F: FnMut(&[u8])>(
+        &self,
+        mut handler: F,
+        fragment_limit: usize,
+    ) -> common::Result<i32> {
+        self.sink.handler.set(&mut handler as *mut F as *mut c_void);
+        self.sink.call.set(Some(call_handler::<F>));
+        let fragments_read = unsafe {
+            aeron::aeron_subscription_poll(
+                self.resource.get(),
+                Some(aeron::aeron_fragment_assembler_handler),
+                self.assembler.get() as *mut c_void,
+                fragment_limit,
+            )
+        };
+        self.sink.call.set(None);
+        self.sink.handler.set(ptr::null_mut());
+        if let Some(payload) = self.sink.panic.take() {
+            panic::resume_unwind(payload);
+        }
+
+        if fragments_read < 0 {
+            return Err(format!(
+                "failed to poll subscription error code {fragments_read}: {}",
+                aeron_errmsg()
+            )
+            .into());
+        }
+        Ok(fragments_read)
+    }
+
+    /// Polls fragments, letting `handler` decide whether each one is consumed and whether
+    /// polling continues. Fragments are delivered as they arrive, without reassembly.
+    pub fn controlled_poll<F: FnMut(&[u8]) -> ControlledPollAction>(
+        &self,
+        handler: F,
+        fragment_limit: usize,
+    ) -> common::Result<i32> {
+        let mut state = ControlledHandler {
+            handler,
+            panic: None,
+        };
+        let fragments_read = unsafe {
+            aeron::aeron_subscription_controlled_poll(
+                self.resource.get(),
+                Some(on_controlled_fragment::<F>),
+                &mut state as *mut ControlledHandler<F> as *mut c_void,
+                fragment_limit,
+            )
+        };
+        if let Some(payload) = state.panic {
+            panic::resume_unwind(payload);
+        }
+        if fragments_read < 0 {
+            return Err(format!(
+                "failed to poll subscription error code {fragments_read}: {}",
+                aeron_errmsg()
+            )
+            .into());
+        }
+        Ok(fragments_read)
+    }
+
+    /// Polls for messages and decodes each one in place from the fragment buffer.
+    ///
+    /// Messages that fail to decode are skipped and the first decode error is returned once
+    /// the poll has completed. Messages that borrow from the fragment, such as `&str`, can be
+    /// decoded inside a [`poll`](Self::poll) handler instead.
+    pub fn poll_msgs<T: DecodeOwned>(
+        &self,
+        mut handler: impl FnMut(T),
+        fragment_limit: usize,
+    ) -> common::Result<i32> {
+        let mut first_error = None;
+        let fragments_read = self.poll(
+            |buffer| match T::decode(buffer) {
+                Ok(msg) => handler(msg),
+                Err(error) => {
+                    first_error.get_or_insert(error);
+                }
+            },
+            fragment_limit,
+        )?;
+
+        match first_error {
+            Some(error) => Err(error.into()),
+            None => Ok(fragments_read),
+        }
+    }
+}
@@ -0,0 +1,268 @@
+use libaeron_sys as aeron;
+
+use std::ffi::{c_void, CStr, CString};
+use std::fmt;
+use std::os::raw::c_char;
+use std::path::Path;
+use std::slice;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use crate::client::{aeron_errmsg, AeronClientContext};
+use crate::common;
+use crate::common::ManagedCResource;
+
+/// Major CnC layout version this crate understands.
+pub const CNC_MAJOR_VERSION: i32 = 0;
+
+/// Values read from the header of a driver's `cnc.dat`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CncConstants {
+    pub cnc_version: i32,
+    pub to_driver_buffer_length: i32,
+    pub to_clients_buffer_length: i32,
+    pub counter_metadata_buffer_length: i32,
+    pub counter_values_buffer_length: i32,
+    pub error_log_buffer_length: i32,
+    pub client_liveness_timeout: Duration,
+    pub start_timestamp_ms: i64,
+    pub pid: i64,
+    pub file_page_size: i32,
+}
+
+impl CncConstants {
+    pub fn major_version(&self) -> i32 {
+        (self.cnc_version >> 16) & 0xFF
+    }
+
+    /// The CnC version as `major.minor.patch`.
+    pub fn version(&self) -> String {
+        format!(
+            "{}.{}.{}",
+            self.major_version(),
+            (self.cnc_version >> 8) & 0xFF,
+            self.cnc_version & 0xFF
+        )
+    }
+}
+
+/// A distinct error observed by the driver, with how often and when it was seen.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ErrorLogEntry {
+    pub observation_count: i32,
+    pub first_observation_timestamp_ms: i64,
+    pub last_observation_timestamp_ms: i64,
+    pub message: String,
+}
+
+unsafe extern "C" fn on_error_log_entry(
+    observation_count: i32,
+    first_observation_timestamp: i64,
+    last_observation_timestamp: i64,
+    error: *const c_char,
+    error_length: usize,
+    clientd: *mut c_void,
+) {
+    let entries = &mut *(clientd as *mut Vec<ErrorLogEntry>);
+    let message = slice::from_raw_parts(error as *const u8, error_length);
+    entries.push(ErrorLogEntry {
+        observation_count,
+        first_observation_timestamp_ms: first_observation_timestamp,
+        last_observation_timestamp_ms: last_observation_timestamp,
+        message: String::from_utf8_lossy(message).into_owned(),
+    });
+}
+
+/// A read-only mapping of a driver's CnC file, used to inspect a running driver without
+/// registering as a client.
+pub struct CncFile {
+    resource: ManagedCResource<aeron::aeron_cnc_t>,
+}
+
+impl CncFile {
+    /// Maps `cnc.dat` in `aeron_dir`, waiting up to `timeout` for the driver to create it.
+    pub fn open(aeron_dir: &str, timeout: Duration) -> common::Result<Self> {
+        let base_path = CString::new(aeron_dir)?;
+        let resource = ManagedCResource::new(
+            |cnc| unsafe {
+                aeron::aeron_cnc_init(cnc, base_path.as_ptr(), timeout.as_millis() as i64)
+            },
+            |cnc| {
+                unsafe { aeron::aeron_cnc_close(cnc) };
+                0
+            },
+        )
+        .map_err(|error_code| {
+            format!(
+                "failed to open cnc file in {aeron_dir} error code {error_code}: {}",
+                aeron_errmsg()
+            )
+        })?;
+
+        Ok(Self { resource })
+    }
+
+    /// The aeron dir a client would use when none is configured.
+    pub fn default_dir() -> common::Result<String> {
+        Ok(AeronClientContext::new()?.get_dir())
+    }
+
+    pub fn filename(&self) -> String {
+        unsafe { CStr::from_ptr(aeron::aeron_cnc_filename(self.resource.get())) }
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    pub fn constants(&self) -> common::Result<CncConstants> {
+        let mut constants: aeron::aeron_cnc_constants_t = unsafe { std::mem::zeroed() };
+        let result = unsafe { aeron::aeron_cnc_constants(self.resource.get(), &mut constants) };
+        if result < 0 {
+            return Err(format!(
+                "failed to read cnc constants error code {result}: {}",
+                aeron_errmsg()
+            )
+            .into());
+        }
+
+        Ok(CncConstants {
+            cnc_version: constants.cnc_version,
+            to_driver_buffer_length: constants.to_driver_buffer_length,
+            to_clients_buffer_length: constants.to_clients_buffer_length,
+            counter_metadata_buffer_length: constants.counter_metadata_buffer_length,
+            counter_values_buffer_length: constants.counter_values_buffer_length,
+            error_log_buffer_length: constants.error_log_buffer_length,
+            client_liveness_timeout: Duration::from_nanos(
+                constants.client_liveness_timeout.max(0) as u64
+            ),
+            start_timestamp_ms: constants.start_timestamp,
+            pid: constants.pid,
+            file_page_size: constants.file_page_size,
+        })
+    }
+
+    /// Epoch milliseconds of the driver's last heartbeat on the to-driver buffer.
+    pub fn driver_heartbeat_ms(&self) -> i64 {
+        unsafe { aeron::aeron_cnc_to_driver_heartbeat(self.resource.get()) }
+    }
+
+    /// Time since the driver last heartbeated, measured against the system clock.
+    pub fn driver_heartbeat_age(&self) -> Duration {
+        let now_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as i64;
+        Duration::from_millis((now_ms - self.driver_heartbeat_ms()).max(0) as u64)
+    }
+
+    /// Reads the distinct error log, keeping entries last observed at or after
+    /// `since_timestamp_ms` (epoch milliseconds).
+    pub fn read_error_log(&self, since_timestamp_ms: i64) -> Vec<ErrorLogEntry> {
+        let mut entries = Vec::new();
+        unsafe {
+            aeron::aeron_cnc_error_log_read(
+                self.resource.get(),
+                Some(on_error_log_entry),
+                &mut entries as *mut Vec<ErrorLogEntry> as *mut c_void,
+                since_timestamp_ms,
+            )
+        };
+        entries
+    }
+
+    /// Raw counters reader over the driver's counters, valid while this file is open.
+    pub fn counters_reader(&self) -> *mut aeron::aeron_counters_reader_t {
+        unsafe { aeron::aeron_cnc_counters_reader(self.resource.get()) }
+    }
+
+    pub(crate) fn get(&self) -> *mut aeron::aeron_cnc_t {
+        self.resource.get()
+    }
+}
+
+/// Outcome of checking whether a driver is running in an aeron dir.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DriverStatus {
+    Active {
+        pid: i64,
+        heartbeat_age: Duration,
+    },
+    NoCncFile,
+    /// The CnC file exists but could not be mapped, e.g. it is still being created.
+    Unavailable(String),
+    VersionMismatch {
+        found: String,
+    },
+    StaleHeartbeat {
+        pid: i64,
+        heartbeat_age: Duration,
+    },
+}
+
+impl DriverStatus {
+    pub fn is_active(&self) -> bool {
+        matches!(self, DriverStatus::Active { .. })
+    }
+}
+
+impl fmt::Display for DriverStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DriverStatus::Active { pid, heartbeat_age } => write!(
+                f,
+                "driver active, pid {pid}, last heartbeat {}ms ago",
+                heartbeat_age.as_millis()
+            ),
+            DriverStatus::NoCncFile => write!(f, "no cnc file found"),
+            DriverStatus::Unavailable(reason) => write!(f, "cnc file unavailable: {reason}"),
+            DriverStatus::VersionMismatch { found } => write!(
+                f,
+                "cnc version mismatch, found {found} but expected major version {CNC_MAJOR_VERSION}"
+            ),
+            DriverStatus::StaleHeartbeat { pid, heartbeat_age } => write!(
+                f,
+                "driver heartbeat stale, pid {pid}, last heartbeat {}ms ago",
+                heartbeat_age.as_millis()
+            ),
+        }
+    }
+}
+
+/// Checks whether a driver is running in `aeron_dir`, treating it as dead when it has not
+/// heartbeated within `timeout`.
+pub fn check_driver(aeron_dir: &str, timeout: Duration) -> DriverStatus {
+    if !Path::new(aeron_dir).join("cnc.dat").exists() {
+        return DriverStatus::NoCncFile;
+    }
+
+    let cnc = match CncFile::open(aeron_dir, Duration::ZERO) {
+        Ok(cnc) => cnc,
+        Err(error) => return DriverStatus::Unavailable(error.to_string()),
+    };
+    let constants = match cnc.constants() {
+        Ok(constants) => constants,
+        Err(error) => return DriverStatus::Unavailable(error.to_string()),
+    };
+    if constants.major_version() != CNC_MAJOR_VERSION {
+        return DriverStatus::VersionMismatch {
+            found: constants.version(),
+        };
+    }
+
+    let heartbeat_age = cnc.driver_heartbeat_age();
+    if heartbeat_age > timeout {
+        return DriverStatus::StaleHeartbeat {
+            pid: constants.pid,
+            heartbeat_age,
+        };
+    }
+    DriverStatus::Active {
+        pid: constants.pid,
+        heartbeat_age,
+    }
+}
+
+/// `true` when a driver in `aeron_dir` has heartbeated within `timeout`.
+pub fn is_driver_active(aeron_dir: &str, timeout: Duration) -> bool {
+    check_driver(aeron_dir, timeout).is_active()
+}
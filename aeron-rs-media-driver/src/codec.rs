@@ -0,0 +1,203 @@
+use std::fmt;
+
+/// A fixed-layout message that can be written directly into a claimed term buffer.
+pub trait Encode {
+    /// Number of bytes `encode` will write.
+    fn encoded_length(&self) -> usize;
+
+    /// Writes the message into `buffer`, which is exactly `encoded_length` bytes long.
+    fn encode(&self, buffer: &mut [u8]);
+}
+
+/// A message that can be read directly out of a fragment buffer, possibly borrowing from it.
+pub trait Decode<'a>: Sized {
+    fn decode(buffer: &'a [u8]) -> Result<Self, DecodeError>;
+}
+
+/// A message that owns its data and so can be decoded from a buffer of any lifetime.
+pub trait DecodeOwned: for<'a> Decode<'a> {}
+
+impl<T: for<'a> Decode<'a>> DecodeOwned for T {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The buffer is shorter than the message layout requires.
+    BufferTooShort { required: usize, actual: usize },
+    /// The buffer holds a value the message layout does not allow.
+    Invalid(String),
+}
+
+impl DecodeError {
+    /// Returns an error unless `buffer` holds at least `required` bytes.
+    pub fn check_length(buffer: &[u8], required: usize) -> Result<(), DecodeError> {
+        if buffer.len() < required {
+            return Err(DecodeError::BufferTooShort {
+                required,
+                actual: buffer.len(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::BufferTooShort { required, actual } => write!(
+                f,
+                "buffer too short to decode message, required {required} bytes but got {actual}"
+            ),
+            DecodeError::Invalid(reason) => write!(f, "invalid message {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl Encode for [u8] {
+    fn encoded_length(&self) -> usize {
+        self.len()
+    }
+
+    fn encode(&self, buffer: &mut [u8]) {
+        buffer.copy_from_slice(self);
+    }
+}
+
+impl<'a> Decode<'a> for &'a [u8] {
+    fn decode(buffer: &'a [u8]) -> Result<Self, DecodeError> {
+        Ok(buffer)
+    }
+}
+
+impl Encode for str {
+    fn encoded_length(&self) -> usize {
+        self.len()
+    }
+
+    fn encode(&self, buffer: &mut [u8]) {
+        buffer.copy_from_slice(self.as_bytes());
+    }
+}
+
+impl<'a> Decode<'a> for &'a str {
+    fn decode(buffer: &'a [u8]) -> Result<Self, DecodeError> {
+        std::str::from_utf8(buffer).map_err(|error| DecodeError::Invalid(error.to_string()))
+    }
+}
+
+macro_rules! impl_primitive_codec {
+    ($($ty:ty),*) => {
+        $(
+            impl Encode for $ty {
+                fn encoded_length(&self) -> usize {
+                    std::mem::size_of::<$ty>()
+                }
+
+                fn encode(&self, buffer: &mut [u8]) {
+                    buffer.copy_from_slice(&self.to_le_bytes());
+                }
+            }
+
+            impl Decode<'_> for $ty {
+                fn decode(buffer: &[u8]) -> Result<Self, DecodeError> {
+                    const LENGTH: usize = std::mem::size_of::<$ty>();
+                    DecodeError::check_length(buffer, LENGTH)?;
+                    let mut bytes = [0u8; LENGTH];
+                    bytes.copy_from_slice(&buffer[..LENGTH]);
+                    Ok(<$ty>::from_le_bytes(bytes))
+                }
+            }
+        )*
+    };
+}
+
+impl_primitive_codec!(u8, u16, u32, u64, i8, i16, i32, i64, f32, f64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Quote {
+        instrument_id: u32,
+        price: i64,
+    }
+
+    impl Encode for Quote {
+        fn encoded_length(&self) -> usize {
+            12
+        }
+
+        fn encode(&self, buffer: &mut [u8]) {
+            self.instrument_id.encode(&mut buffer[0..4]);
+            self.price.encode(&mut buffer[4..12]);
+        }
+    }
+
+    impl Decode<'_> for Quote {
+        fn decode(buffer: &[u8]) -> Result<Self, DecodeError> {
+            DecodeError::check_length(buffer, 12)?;
+            Ok(Quote {
+                instrument_id: u32::decode(&buffer[0..4])?,
+                price: i64::decode(&buffer[4..12])?,
+            })
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let quote = Quote {
+            instrument_id: 7,
+            price: -42,
+        };
+        let mut buffer = vec![0u8; quote.encoded_length()];
+        quote.encode(&mut buffer);
+
+        let decoded = Quote::decode(&buffer).unwrap();
+        assert_eq!(decoded.instrument_id, 7);
+        assert_eq!(decoded.price, -42);
+    }
+
+    struct Tagged<'a> {
+        id: u16,
+        tag: &'a str,
+    }
+
+    impl<'a> Decode<'a> for Tagged<'a> {
+        fn decode(buffer: &'a [u8]) -> Result<Self, DecodeError> {
+            DecodeError::check_length(buffer, 2)?;
+            Ok(Tagged {
+                id: u16::decode(&buffer[0..2])?,
+                tag: <&str>::decode(&buffer[2..])?,
+            })
+        }
+    }
+
+    #[test]
+    fn borrowed_decode() {
+        let mut buffer = vec![0u8; 2 + "quote".len()];
+        9u16.encode(&mut buffer[0..2]);
+        "quote".encode(&mut buffer[2..]);
+
+        let tagged = Tagged::decode(&buffer).unwrap();
+        assert_eq!(tagged.id, 9);
+        assert_eq!(tagged.tag, "quote");
+        assert_eq!(tagged.tag.as_ptr(), buffer[2..].as_ptr());
+
+        assert!(matches!(
+            <&str>::decode(&[0xff, 0xfe]),
+            Err(DecodeError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn short_buffer() {
+        assert_eq!(
+            Quote::decode(&[0u8; 4]).err(),
+            Some(DecodeError::BufferTooShort {
+                required: 12,
+                actual: 4
+            })
+        );
+    }
+}
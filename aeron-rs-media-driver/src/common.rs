@@ -1,5 +1,6 @@
 use std::any::type_name;
 use std::ptr;
+use std::sync::{Arc, Mutex};
 
 type Error = Box<dyn std::error::Error>;
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -40,12 +41,82 @@ impl<T> Drop for ManagedCResource<T> {
                 type_name::<T>(),
                 result
             );
-        } else {
-            println!(
-                "Closed resource of type {} with success code {}",
-                type_name::<T>(),
-                result
-            );
         }
     }
 }
+
+/// Rust values looked up by a C pointer, for driver callbacks that are handed a pointer
+/// such as the driver context but no client data to hold them.
+pub(crate) struct CallbackRegistry<T: ?Sized> {
+    entries: Mutex<Vec<(usize, Arc<T>)>>,
+}
+
+impl<T: ?Sized> CallbackRegistry<T> {
+    pub(crate) const fn new() -> Self {
+        Self {
+            entries: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub(crate) fn insert<C>(&self, key: *const C, value: Arc<T>) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|(entry_key, _)| *entry_key != key as usize);
+        entries.push((key as usize, value));
+    }
+
+    pub(crate) fn get<C>(&self, key: *const C) -> Option<Arc<T>> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .iter()
+            .find(|(entry_key, _)| *entry_key == key as usize)
+            .map(|(_, value)| Arc::clone(value))
+    }
+
+    pub(crate) fn remove<C>(&self, key: *const C) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|(entry_key, _)| *entry_key != key as usize);
+    }
+}
+
+/// Formats epoch milliseconds as a UTC `YYYY-MM-DD HH:MM:SS.mmm` timestamp.
+pub fn format_epoch_ms(epoch_ms: i64) -> String {
+    let days = epoch_ms.div_euclid(86_400_000);
+    let ms_of_day = epoch_ms.rem_euclid(86_400_000);
+
+    // Civil-from-days, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}.{:03}",
+        year,
+        month,
+        day,
+        ms_of_day / 3_600_000,
+        ms_of_day / 60_000 % 60,
+        ms_of_day / 1000 % 60,
+        ms_of_day % 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_epoch() {
+        assert_eq!(format_epoch_ms(0), "1970-01-01 00:00:00.000");
+        assert_eq!(format_epoch_ms(951_782_400_123), "2000-02-29 00:00:00.123");
+        assert_eq!(
+            format_epoch_ms(1_729_260_245_678),
+            "2024-10-18 14:04:05.678"
+        );
+    }
+}
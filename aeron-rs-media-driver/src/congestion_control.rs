@@ -0,0 +1,280 @@
+use libaeron_driver_sys as aeron_driver;
+
+use std::ffi::c_void;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use crate::common::CallbackRegistry;
+use crate::sockaddr;
+
+/// The image a congestion control strategy is created for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CongestionControlImage {
+    pub stream_id: i32,
+    pub session_id: i32,
+    pub registration_id: i64,
+    pub term_length: i32,
+    pub sender_mtu_length: i32,
+    pub control_address: Option<SocketAddr>,
+    pub source_address: Option<SocketAddr>,
+}
+
+/// Positions of an image as the receiver tracks the rebuild of its terms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrackRebuild {
+    pub new_consumption_position: i64,
+    pub last_status_message_position: i64,
+    pub hwm_position: i64,
+    pub starting_rebuild_position: i64,
+    pub ending_rebuild_position: i64,
+    pub loss_occurred: bool,
+}
+
+/// The receiver window to advertise and whether to send a status message straight away.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowUpdate {
+    pub window_length: i32,
+    pub force_status_message: bool,
+}
+
+/// Sizes the receiver window of an image from round trip times and loss.
+///
+/// Strategies run on the receiver agent thread of the driver.
+pub trait CongestionControlStrategy: Send {
+    /// Window advertised before any rebuild has been tracked.
+    fn initial_window_length(&self) -> i32;
+
+    fn max_window_length(&self) -> i32 {
+        self.initial_window_length()
+    }
+
+    /// Whether the receiver should send an RTT measurement now.
+    fn should_measure_rtt(&self, _now_ns: i64) -> bool {
+        false
+    }
+
+    fn on_rttm_sent(&mut self, _now_ns: i64) {}
+
+    /// Called with the round trip time measured by a reply from the sender.
+    fn on_rttm(&mut self, _now_ns: i64, _rtt_ns: i64, _source_address: Option<SocketAddr>) {}
+
+    /// Called as the image is rebuilt, returning the window to advertise in the next
+    /// status message.
+    fn on_track_rebuild(&mut self, _rebuild: &TrackRebuild, _now_ns: i64) -> WindowUpdate {
+        WindowUpdate {
+            window_length: self.initial_window_length(),
+            force_status_message: false,
+        }
+    }
+
+    /// Called when a track rebuild schedules a status message, because it was forced,
+    /// the consumption position moved a quarter of the window past the last one or the
+    /// window changed. The receiver sends it on its next duty cycle.
+    fn on_status_message(&mut self, _now_ns: i64, _position: i64, _window_length: i32) {}
+}
+
+/// Whether the receiver schedules a status message after a track rebuild, mirroring
+/// `aeron_publication_image_track_rebuild`.
+fn schedules_status_message(
+    rebuild: &TrackRebuild,
+    update: &WindowUpdate,
+    last_window_length: i32,
+) -> bool {
+    let threshold = i64::from(update.window_length / 4);
+    update.force_status_message
+        || rebuild.new_consumption_position > rebuild.last_status_message_position + threshold
+        || update.window_length != last_window_length
+}
+
+/// Creates a strategy for each image.
+pub type CongestionControlSupplier =
+    dyn Fn(&CongestionControlImage) -> Box<dyn CongestionControlStrategy> + Send + Sync;
+
+static SUPPLIERS: CallbackRegistry<CongestionControlSupplier> = CallbackRegistry::new();
+
+struct StrategyState {
+    strategy: Box<dyn CongestionControlStrategy>,
+    // The image starts out advertising the initial window
+    last_window_length: i32,
+}
+
+pub(crate) fn set_supplier(
+    context: *mut aeron_driver::aeron_driver_context_t,
+    supplier: Arc<CongestionControlSupplier>,
+) -> i32 {
+    SUPPLIERS.insert(context, supplier);
+    unsafe {
+        aeron_driver::aeron_driver_context_set_congestioncontrol_supplier(
+            context,
+            Some(supplier_fn),
+        )
+    }
+}
+
+pub(crate) fn remove_supplier(context: *mut aeron_driver::aeron_driver_context_t) {
+    SUPPLIERS.remove(context);
+}
+
+#[allow(clippy::too_many_arguments)]
+unsafe extern "C" fn supplier_fn(
+    strategy: *mut *mut aeron_driver::aeron_congestion_control_strategy_t,
+    _channel: *mut aeron_driver::aeron_udp_channel_t,
+    stream_id: i32,
+    session_id: i32,
+    registration_id: i64,
+    term_length: i32,
+    sender_mtu_length: i32,
+    control_address: *mut aeron_driver::sockaddr_storage,
+    source_address: *mut aeron_driver::sockaddr_storage,
+    context: *mut aeron_driver::aeron_driver_context_t,
+    _counters_manager: *mut aeron_driver::aeron_counters_manager_t,
+) -> i32 {
+    let Some(supplier) = SUPPLIERS.get(context) else {
+        return -1;
+    };
+    let image = CongestionControlImage {
+        stream_id,
+        session_id,
+        registration_id,
+        term_length,
+        sender_mtu_length,
+        control_address: sockaddr::from_raw(control_address),
+        source_address: sockaddr::from_raw(source_address),
+    };
+
+    let congestion_control = supplier(&image);
+    let state = Box::new(StrategyState {
+        last_window_length: congestion_control.initial_window_length(),
+        strategy: congestion_control,
+    });
+    let mut raw: aeron_driver::aeron_congestion_control_strategy_t = std::mem::zeroed();
+    raw.should_measure_rtt = Some(should_measure_rtt);
+    raw.on_rttm_sent = Some(on_rttm_sent);
+    raw.on_rttm = Some(on_rttm);
+    raw.on_track_rebuild = Some(on_track_rebuild);
+    raw.initial_window_length = Some(initial_window_length);
+    raw.max_window_length = Some(max_window_length);
+    raw.fini = Some(fini);
+    raw.state = Box::into_raw(state) as *mut c_void;
+    *strategy = Box::into_raw(Box::new(raw));
+    0
+}
+
+unsafe extern "C" fn should_measure_rtt(state: *mut c_void, now_ns: i64) -> bool {
+    let state = &*(state as *const StrategyState);
+    state.strategy.should_measure_rtt(now_ns)
+}
+
+unsafe extern "C" fn on_rttm_sent(state: *mut c_void, now_ns: i64) {
+    let state = &mut *(state as *mut StrategyState);
+    state.strategy.on_rttm_sent(now_ns);
+}
+
+unsafe extern "C" fn on_rttm(
+    state: *mut c_void,
+    now_ns: i64,
+    rtt_ns: i64,
+    source_address: *mut aeron_driver::sockaddr_storage,
+) {
+    let state = &mut *(state as *mut StrategyState);
+    state
+        .strategy
+        .on_rttm(now_ns, rtt_ns, sockaddr::from_raw(source_address));
+}
+
+#[allow(clippy::too_many_arguments)]
+unsafe extern "C" fn on_track_rebuild(
+    state: *mut c_void,
+    should_force_sm: *mut bool,
+    now_ns: i64,
+    new_consumption_position: i64,
+    last_sm_position: i64,
+    hwm_position: i64,
+    starting_rebuild_position: i64,
+    ending_rebuild_position: i64,
+    loss_occurred: bool,
+) -> i32 {
+    let state = &mut *(state as *mut StrategyState);
+    let rebuild = TrackRebuild {
+        new_consumption_position,
+        last_status_message_position: last_sm_position,
+        hwm_position,
+        starting_rebuild_position,
+        ending_rebuild_position,
+        loss_occurred,
+    };
+    let update = state.strategy.on_track_rebuild(&rebuild, now_ns);
+    if schedules_status_message(&rebuild, &update, state.last_window_length) {
+        state.last_window_length = update.window_length;
+        state.strategy.on_status_message(
+            now_ns,
+            rebuild.new_consumption_position,
+            update.window_length,
+        );
+    }
+    *should_force_sm = update.force_status_message;
+    update.window_length
+}
+
+unsafe extern "C" fn initial_window_length(state: *mut c_void) -> i32 {
+    let state = &*(state as *const StrategyState);
+    state.strategy.initial_window_length()
+}
+
+unsafe extern "C" fn max_window_length(state: *mut c_void) -> i32 {
+    let state = &*(state as *const StrategyState);
+    state.strategy.max_window_length()
+}
+
+unsafe extern "C" fn fini(strategy: *mut aeron_driver::aeron_congestion_control_strategy_t) -> i32 {
+    let strategy = Box::from_raw(strategy);
+    drop(Box::from_raw(strategy.state as *mut StrategyState));
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rebuild(new_consumption_position: i64) -> TrackRebuild {
+        TrackRebuild {
+            new_consumption_position,
+            last_status_message_position: 1000,
+            hwm_position: 2000,
+            starting_rebuild_position: 1000,
+            ending_rebuild_position: 2000,
+            loss_occurred: false,
+        }
+    }
+
+    fn update(window_length: i32, force_status_message: bool) -> WindowUpdate {
+        WindowUpdate {
+            window_length,
+            force_status_message,
+        }
+    }
+
+    #[test]
+    fn schedules_status_message_like_the_receiver() {
+        assert!(!schedules_status_message(
+            &rebuild(1256),
+            &update(1024, false),
+            1024
+        ));
+        assert!(schedules_status_message(
+            &rebuild(1257),
+            &update(1024, false),
+            1024
+        ));
+        assert!(schedules_status_message(
+            &rebuild(1000),
+            &update(1024, true),
+            1024
+        ));
+        assert!(schedules_status_message(
+            &rebuild(1000),
+            &update(2048, false),
+            1024
+        ));
+    }
+}
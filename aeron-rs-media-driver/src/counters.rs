@@ -0,0 +1,319 @@
+use libaeron_sys as aeron;
+
+use std::ffi::c_void;
+use std::os::raw::c_char;
+use std::slice;
+
+use regex::Regex;
+
+use crate::cnc::CncFile;
+
+// Counter type ids, mirroring `aeron_counters.h`.
+pub const SYSTEM_COUNTER_TYPE_ID: i32 = 0;
+pub const PUBLISHER_LIMIT_TYPE_ID: i32 = 1;
+pub const SENDER_POSITION_TYPE_ID: i32 = 2;
+pub const RECEIVER_HWM_TYPE_ID: i32 = 3;
+pub const SUBSCRIBER_POSITION_TYPE_ID: i32 = 4;
+pub const RECEIVER_POSITION_TYPE_ID: i32 = 5;
+pub const SEND_CHANNEL_STATUS_TYPE_ID: i32 = 6;
+pub const RECEIVE_CHANNEL_STATUS_TYPE_ID: i32 = 7;
+pub const SENDER_LIMIT_TYPE_ID: i32 = 9;
+pub const PER_IMAGE_TYPE_ID: i32 = 10;
+pub const CLIENT_HEARTBEAT_TIMESTAMP_TYPE_ID: i32 = 11;
+pub const PUBLISHER_POSITION_TYPE_ID: i32 = 12;
+pub const SENDER_BPE_TYPE_ID: i32 = 13;
+pub const LOCAL_SOCKET_ADDRESS_TYPE_ID: i32 = 14;
+pub const NAME_RESOLVER_NEIGHBORS_TYPE_ID: i32 = 15;
+pub const NAME_RESOLVER_CACHE_ENTRIES_TYPE_ID: i32 = 16;
+
+/// Identifies the stream a position counter belongs to, decoded from the counter key.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StreamKey {
+    pub registration_id: i64,
+    pub session_id: i32,
+    pub stream_id: i32,
+    pub channel: String,
+}
+
+impl StreamKey {
+    /// Decodes the key layout shared by publication and image position counters.
+    pub fn decode(key: &[u8]) -> Option<StreamKey> {
+        let registration_id = i64::from_le_bytes(key.get(0..8)?.try_into().ok()?);
+        let session_id = i32::from_le_bytes(key.get(8..12)?.try_into().ok()?);
+        let stream_id = i32::from_le_bytes(key.get(12..16)?.try_into().ok()?);
+        let channel = decode_channel(key.get(16..)?)?;
+        Some(StreamKey {
+            registration_id,
+            session_id,
+            stream_id,
+            channel,
+        })
+    }
+}
+
+/// The bound address of a channel's socket, decoded from a local socket address counter key.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LocalSocketAddressKey {
+    /// Id of the channel status counter of the channel the socket belongs to.
+    pub channel_status_id: i32,
+    pub address: String,
+}
+
+impl LocalSocketAddressKey {
+    /// Decodes the channel status counter id followed by the length-prefixed address.
+    pub fn decode(key: &[u8]) -> Option<LocalSocketAddressKey> {
+        let channel_status_id = i32::from_le_bytes(key.get(0..4)?.try_into().ok()?);
+        let address = decode_channel(key.get(4..)?)?;
+        Some(LocalSocketAddressKey {
+            channel_status_id,
+            address,
+        })
+    }
+}
+
+/// Reads a length-prefixed channel, truncating it to the bytes available in the key.
+fn decode_channel(key: &[u8]) -> Option<String> {
+    let length = i32::from_le_bytes(key.get(0..4)?.try_into().ok()?);
+    let channel = &key[4..];
+    let length = (length.max(0) as usize).min(channel.len());
+    Some(String::from_utf8_lossy(&channel[..length]).into_owned())
+}
+
+fn is_stream_type_id(type_id: i32) -> bool {
+    matches!(
+        type_id,
+        PUBLISHER_LIMIT_TYPE_ID
+            | SENDER_POSITION_TYPE_ID
+            | RECEIVER_HWM_TYPE_ID
+            | SUBSCRIBER_POSITION_TYPE_ID
+            | RECEIVER_POSITION_TYPE_ID
+            | SENDER_LIMIT_TYPE_ID
+            | PER_IMAGE_TYPE_ID
+            | PUBLISHER_POSITION_TYPE_ID
+            | SENDER_BPE_TYPE_ID
+    )
+}
+
+fn is_channel_type_id(type_id: i32) -> bool {
+    matches!(
+        type_id,
+        SEND_CHANNEL_STATUS_TYPE_ID | RECEIVE_CHANNEL_STATUS_TYPE_ID
+    )
+}
+
+/// A snapshot of one allocated counter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CounterEntry {
+    pub id: i32,
+    pub type_id: i32,
+    pub value: i64,
+    pub label: String,
+    pub key: Vec<u8>,
+}
+
+impl CounterEntry {
+    /// The stream this counter tracks, for publication and image position counters.
+    pub fn stream_key(&self) -> Option<StreamKey> {
+        if !is_stream_type_id(self.type_id) {
+            return None;
+        }
+        StreamKey::decode(&self.key)
+    }
+
+    /// The channel this counter relates to, for stream and channel status counters.
+    pub fn channel(&self) -> Option<String> {
+        if is_channel_type_id(self.type_id) {
+            return decode_channel(&self.key);
+        }
+        self.stream_key().map(|key| key.channel)
+    }
+
+    /// The bound socket address, for local socket address counters.
+    pub fn local_socket_address(&self) -> Option<LocalSocketAddressKey> {
+        if self.type_id != LOCAL_SOCKET_ADDRESS_TYPE_ID {
+            return None;
+        }
+        LocalSocketAddressKey::decode(&self.key)
+    }
+}
+
+unsafe extern "C" fn on_counter(
+    value: i64,
+    id: i32,
+    type_id: i32,
+    key: *const u8,
+    key_length: usize,
+    label: *const c_char,
+    label_length: usize,
+    clientd: *mut c_void,
+) {
+    let counters = &mut *(clientd as *mut Vec<CounterEntry>);
+    let key = if key.is_null() {
+        Vec::new()
+    } else {
+        slice::from_raw_parts(key, key_length).to_vec()
+    };
+    let label = slice::from_raw_parts(label as *const u8, label_length);
+    counters.push(CounterEntry {
+        id,
+        type_id,
+        value,
+        label: String::from_utf8_lossy(label).into_owned(),
+        key,
+    });
+}
+
+/// Reads every allocated counter from the driver's CnC file.
+pub fn read_counters(cnc: &CncFile) -> Vec<CounterEntry> {
+    let mut counters = Vec::new();
+    unsafe {
+        aeron::aeron_counters_reader_foreach_counter(
+            cnc.counters_reader(),
+            Some(on_counter),
+            &mut counters as *mut Vec<CounterEntry> as *mut c_void,
+        )
+    };
+    counters
+}
+
+/// Selects counters by type id, label, session, stream and channel. Unset fields match
+/// every counter.
+#[derive(Debug, Clone, Default)]
+pub struct CounterFilter {
+    pub type_id: Option<i32>,
+    pub label: Option<Regex>,
+    pub session_id: Option<i32>,
+    pub stream_id: Option<i32>,
+    /// Substring the counter's channel must contain.
+    pub channel: Option<String>,
+}
+
+impl CounterFilter {
+    pub fn matches(&self, counter: &CounterEntry) -> bool {
+        if self
+            .type_id
+            .is_some_and(|type_id| type_id != counter.type_id)
+        {
+            return false;
+        }
+        if let Some(label) = &self.label {
+            if !label.is_match(&counter.label) {
+                return false;
+            }
+        }
+        if self.session_id.is_some() || self.stream_id.is_some() {
+            let Some(key) = counter.stream_key() else {
+                return false;
+            };
+            if self.session_id.is_some_and(|id| id != key.session_id)
+                || self.stream_id.is_some_and(|id| id != key.stream_id)
+            {
+                return false;
+            }
+        }
+        if let Some(channel) = &self.channel {
+            if !counter
+                .channel()
+                .is_some_and(|counter_channel| counter_channel.contains(channel.as_str()))
+            {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Builds a stream counter key as the driver lays it out: registration id, session id,
+/// stream id, then the length prefixed channel.
+#[cfg(test)]
+pub(crate) fn stream_counter_key(
+    registration_id: i64,
+    session_id: i32,
+    stream_id: i32,
+    channel: &str,
+) -> Vec<u8> {
+    let mut key = Vec::new();
+    key.extend_from_slice(&registration_id.to_le_bytes());
+    key.extend_from_slice(&session_id.to_le_bytes());
+    key.extend_from_slice(&stream_id.to_le_bytes());
+    key.extend_from_slice(&(channel.len() as i32).to_le_bytes());
+    key.extend_from_slice(channel.as_bytes());
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stream_counter(
+        type_id: i32,
+        session_id: i32,
+        stream_id: i32,
+        channel: &str,
+    ) -> CounterEntry {
+        CounterEntry {
+            id: 1,
+            type_id,
+            value: 0,
+            label: format!("sub-pos: 42 {session_id} {stream_id} {channel} @0"),
+            key: stream_counter_key(42, session_id, stream_id, channel),
+        }
+    }
+
+    #[test]
+    fn decode_stream_key() {
+        let counter = stream_counter(SUBSCRIBER_POSITION_TYPE_ID, -7, 1001, "aeron:ipc");
+        assert_eq!(
+            counter.stream_key(),
+            Some(StreamKey {
+                registration_id: 42,
+                session_id: -7,
+                stream_id: 1001,
+                channel: "aeron:ipc".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn decode_local_socket_address_key() {
+        let address = "127.0.0.1:40123";
+        let mut key = Vec::new();
+        key.extend_from_slice(&5i32.to_le_bytes());
+        key.extend_from_slice(&(address.len() as i32).to_le_bytes());
+        key.extend_from_slice(address.as_bytes());
+        let counter = CounterEntry {
+            id: 6,
+            type_id: LOCAL_SOCKET_ADDRESS_TYPE_ID,
+            value: 1,
+            label: format!("rcv-local-addr: {address}"),
+            key,
+        };
+
+        assert_eq!(
+            counter.local_socket_address(),
+            Some(LocalSocketAddressKey {
+                channel_status_id: 5,
+                address: address.to_string(),
+            })
+        );
+        assert_eq!(counter.channel(), None);
+    }
+
+    #[test]
+    fn filter_by_stream_and_channel() {
+        let counter = stream_counter(SUBSCRIBER_POSITION_TYPE_ID, -7, 1001, "aeron:ipc");
+        let filter = CounterFilter {
+            stream_id: Some(1001),
+            channel: Some("ipc".to_string()),
+            label: Some(Regex::new("^sub-pos").unwrap()),
+            ..Default::default()
+        };
+        assert!(filter.matches(&counter));
+
+        let filter = CounterFilter {
+            session_id: Some(8),
+            ..Default::default()
+        };
+        assert!(!filter.matches(&counter));
+    }
+}
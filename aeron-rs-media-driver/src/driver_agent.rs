@@ -0,0 +1,538 @@
+use libaeron_driver_sys as aeron_driver;
+
+use std::ffi::{c_void, CStr};
+use std::mem;
+use std::net::{IpAddr, SocketAddr};
+use std::ptr;
+use std::slice;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crate::common;
+use crate::interceptor::{
+    FRAME_TYPE_DATA, FRAME_TYPE_ERR, FRAME_TYPE_NAK, FRAME_TYPE_PAD, FRAME_TYPE_RES,
+    FRAME_TYPE_RTTM, FRAME_TYPE_SETUP, FRAME_TYPE_SM,
+};
+use crate::sockaddr;
+
+const EVENTS_PER_READ: usize = 100;
+
+// The ring buffer allows a single consumer, so only one forwarder may run at a time
+static FORWARDER_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Drains the C driver agent's event ring buffer on a background thread and forwards each
+/// event to `tracing` under the `aeron_driver_agent` target. Frames are logged at trace
+/// level with their address and type. Commands are logged at debug level with their
+/// correlation id, stream id and channel, name resolution events with their host and
+/// address, and all other events with their length.
+///
+/// Enable the events to record with `AeronContext::set_event_log` first, which keeps the
+/// agent's own log reader stopped, so each event is forwarded exactly once. Only one
+/// forwarder can run at a time. The thread stops when the forwarder is dropped.
+pub struct EventLogForwarder {
+    running: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl EventLogForwarder {
+    pub fn start() -> common::Result<Self> {
+        let ring_buffer = unsafe { aeron_driver::aeron_driver_agent_mpsc_rb() };
+        if ring_buffer.is_null() {
+            return Err("driver agent event log is not enabled".into());
+        }
+        if FORWARDER_RUNNING.swap(true, Ordering::AcqRel) {
+            return Err("driver agent event log is already being forwarded".into());
+        }
+        // The ring buffer lives for the rest of the process
+        let ring_buffer = ring_buffer as usize;
+
+        let running = Arc::new(AtomicBool::new(true));
+        let thread_running = Arc::clone(&running);
+        let thread = std::thread::Builder::new()
+            .name("aeron-event-log".to_string())
+            .spawn(move || {
+                let ring_buffer = ring_buffer as *mut aeron_driver::aeron_mpsc_rb_t;
+                while thread_running.load(Ordering::Acquire) {
+                    let read = unsafe {
+                        aeron_driver::aeron_mpsc_rb_read(
+                            ring_buffer,
+                            Some(on_event),
+                            ptr::null_mut(),
+                            EVENTS_PER_READ,
+                        )
+                    };
+                    if read == 0 {
+                        std::thread::sleep(Duration::from_millis(1));
+                    }
+                }
+            })
+            .inspect_err(|_| FORWARDER_RUNNING.store(false, Ordering::Release))?;
+
+        Ok(Self {
+            running,
+            thread: Some(thread),
+        })
+    }
+}
+
+impl Drop for EventLogForwarder {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Release);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+        FORWARDER_RUNNING.store(false, Ordering::Release);
+    }
+}
+
+fn frame_type_name(frame_type: u32) -> &'static str {
+    match frame_type {
+        FRAME_TYPE_PAD => "PAD",
+        FRAME_TYPE_DATA => "DATA",
+        FRAME_TYPE_NAK => "NAK",
+        FRAME_TYPE_SM => "SM",
+        FRAME_TYPE_ERR => "ERR",
+        FRAME_TYPE_SETUP => "SETUP",
+        FRAME_TYPE_RTTM => "RTTM",
+        FRAME_TYPE_RES => "RES",
+        _ => "UNKNOWN",
+    }
+}
+
+fn event_name(msg_type_id: i32) -> String {
+    let name = unsafe {
+        aeron_driver::aeron_driver_agent_event_name(
+            msg_type_id as aeron_driver::aeron_driver_agent_event_t,
+        )
+    };
+    if name.is_null() {
+        return format!("EVENT_{msg_type_id}");
+    }
+    unsafe { CStr::from_ptr(name) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+// Event ids, mirroring `aeron_driver_agent.h`.
+const FRAME_IN: i32 = 1;
+const FRAME_OUT: i32 = 2;
+const CMD_IN_ADD_PUBLICATION: i32 = 3;
+const CMD_IN_REMOVE_PUBLICATION: i32 = 4;
+const CMD_IN_ADD_SUBSCRIPTION: i32 = 5;
+const CMD_IN_REMOVE_SUBSCRIPTION: i32 = 6;
+const CMD_OUT_PUBLICATION_READY: i32 = 7;
+const CMD_OUT_AVAILABLE_IMAGE: i32 = 8;
+const CMD_OUT_ON_OPERATION_SUCCESS: i32 = 12;
+const CMD_IN_KEEPALIVE_CLIENT: i32 = 13;
+const CMD_OUT_ON_UNAVAILABLE_IMAGE: i32 = 17;
+const CMD_IN_ADD_DESTINATION: i32 = 30;
+const CMD_IN_REMOVE_DESTINATION: i32 = 31;
+const CMD_IN_ADD_EXCLUSIVE_PUBLICATION: i32 = 32;
+const CMD_OUT_EXCLUSIVE_PUBLICATION_READY: i32 = 33;
+const CMD_OUT_ERROR: i32 = 34;
+const CMD_IN_ADD_COUNTER: i32 = 35;
+const CMD_IN_REMOVE_COUNTER: i32 = 36;
+const CMD_OUT_SUBSCRIPTION_READY: i32 = 37;
+const CMD_OUT_COUNTER_READY: i32 = 38;
+const CMD_OUT_ON_UNAVAILABLE_COUNTER: i32 = 39;
+const CMD_IN_CLIENT_CLOSE: i32 = 40;
+const CMD_IN_ADD_RCV_DESTINATION: i32 = 41;
+const CMD_IN_REMOVE_RCV_DESTINATION: i32 = 42;
+const CMD_OUT_ON_CLIENT_TIMEOUT: i32 = 43;
+const CMD_IN_TERMINATE_DRIVER: i32 = 44;
+const NAME_RESOLUTION_NEIGHBOR_ADDED: i32 = 46;
+const NAME_RESOLUTION_NEIGHBOR_REMOVED: i32 = 47;
+const NAME_RESOLUTION_RESOLVE: i32 = 50;
+const NAME_RESOLUTION_LOOKUP: i32 = 52;
+const NAME_RESOLUTION_HOST_NAME: i32 = 53;
+
+// Record header lengths, mirroring the log headers in `aeron_driver_agent.h`.
+const LOG_HEADER_LENGTH: usize = 8;
+const FRAME_LOG_HEADER_LENGTH: usize = 16;
+const CMD_LOG_HEADER_LENGTH: usize = 16;
+const RESOLVE_LOG_HEADER_LENGTH: usize = 32;
+const LOOKUP_LOG_HEADER_LENGTH: usize = 32;
+const HOST_NAME_LOG_HEADER_LENGTH: usize = 24;
+
+/// Offsets of a command's fields from the start of its body, following the structs in
+/// `aeron_control_protocol.h`. A command with a channel carries it after its fixed fields.
+#[derive(Debug, Clone, Copy, Default)]
+struct CommandLayout {
+    correlation_id: Option<usize>,
+    stream_id: Option<usize>,
+    channel_length: Option<usize>,
+}
+
+fn command_layout(msg_type_id: i32) -> Option<CommandLayout> {
+    let layout = |correlation_id, stream_id, channel_length| CommandLayout {
+        correlation_id,
+        stream_id,
+        channel_length,
+    };
+    Some(match msg_type_id {
+        CMD_IN_ADD_PUBLICATION | CMD_IN_ADD_EXCLUSIVE_PUBLICATION => {
+            layout(Some(8), Some(16), Some(20))
+        }
+        CMD_IN_ADD_SUBSCRIPTION => layout(Some(8), Some(24), Some(28)),
+        CMD_IN_ADD_DESTINATION
+        | CMD_IN_REMOVE_DESTINATION
+        | CMD_IN_ADD_RCV_DESTINATION
+        | CMD_IN_REMOVE_RCV_DESTINATION => layout(Some(8), None, Some(24)),
+        CMD_IN_REMOVE_PUBLICATION
+        | CMD_IN_REMOVE_SUBSCRIPTION
+        | CMD_IN_ADD_COUNTER
+        | CMD_IN_REMOVE_COUNTER
+        | CMD_IN_KEEPALIVE_CLIENT
+        | CMD_IN_CLIENT_CLOSE
+        | CMD_IN_TERMINATE_DRIVER => layout(Some(8), None, None),
+        CMD_OUT_PUBLICATION_READY | CMD_OUT_EXCLUSIVE_PUBLICATION_READY => {
+            layout(Some(0), Some(20), None)
+        }
+        CMD_OUT_AVAILABLE_IMAGE => layout(Some(0), Some(12), None),
+        CMD_OUT_ON_UNAVAILABLE_IMAGE => layout(Some(0), Some(16), Some(20)),
+        CMD_OUT_ON_OPERATION_SUCCESS
+        | CMD_OUT_SUBSCRIPTION_READY
+        | CMD_OUT_ERROR
+        | CMD_OUT_COUNTER_READY
+        | CMD_OUT_ON_UNAVAILABLE_COUNTER => layout(Some(0), None, None),
+        CMD_OUT_ON_CLIENT_TIMEOUT => CommandLayout::default(),
+        _ => return None,
+    })
+}
+
+/// An event record read from the agent's ring buffer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum AgentEvent {
+    Frame {
+        time_ns: i64,
+        address: Option<SocketAddr>,
+        frame_type: u32,
+        frame_length: i32,
+    },
+    Command {
+        time_ns: i64,
+        correlation_id: Option<i64>,
+        stream_id: Option<i32>,
+        channel: Option<String>,
+    },
+    NameResolution {
+        time_ns: i64,
+        host: Option<String>,
+        address: Option<String>,
+    },
+    Other {
+        time_ns: i64,
+    },
+}
+
+fn read_i32(bytes: &[u8], offset: usize) -> Option<i32> {
+    let bytes = bytes.get(offset..offset + 4)?;
+    Some(i32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+fn read_i64(bytes: &[u8], offset: usize) -> Option<i64> {
+    let bytes = bytes.get(offset..offset + 8)?;
+    Some(i64::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+fn read_length(bytes: &[u8], offset: usize) -> Option<usize> {
+    usize::try_from(read_i32(bytes, offset)?).ok()
+}
+
+fn read_string(bytes: &[u8], offset: usize, length: usize) -> Option<String> {
+    let bytes = bytes.get(offset..offset.checked_add(length)?)?;
+    Some(String::from_utf8_lossy(bytes).into_owned())
+}
+
+fn read_sockaddr(bytes: &[u8]) -> Option<SocketAddr> {
+    let mut storage: libc::sockaddr_storage = unsafe { mem::zeroed() };
+    let length = bytes.len().min(mem::size_of_val(&storage));
+    unsafe {
+        ptr::copy_nonoverlapping(bytes.as_ptr(), &mut storage as *mut _ as *mut u8, length);
+        sockaddr::from_raw(&storage)
+    }
+}
+
+fn read_ip(bytes: &[u8]) -> Option<IpAddr> {
+    match bytes.len() {
+        4 => Some(IpAddr::from(<[u8; 4]>::try_from(bytes).unwrap())),
+        16 => Some(IpAddr::from(<[u8; 16]>::try_from(bytes).unwrap())),
+        _ => None,
+    }
+}
+
+fn decode_event(msg_type_id: i32, message: &[u8]) -> AgentEvent {
+    // Every event starts with its timestamp
+    let time_ns = read_i64(message, 0).unwrap_or_default();
+    match msg_type_id {
+        FRAME_IN | FRAME_OUT => decode_frame(time_ns, message),
+        NAME_RESOLUTION_NEIGHBOR_ADDED | NAME_RESOLUTION_NEIGHBOR_REMOVED => {
+            AgentEvent::NameResolution {
+                time_ns,
+                host: None,
+                address: message
+                    .get(LOG_HEADER_LENGTH..)
+                    .and_then(read_sockaddr)
+                    .map(|address| address.to_string()),
+            }
+        }
+        NAME_RESOLUTION_RESOLVE => {
+            let resolver_name_length = read_length(message, 16).unwrap_or_default();
+            let host_length = read_length(message, 20).unwrap_or_default();
+            let address_length = read_length(message, 24).unwrap_or_default();
+            let host_offset = RESOLVE_LOG_HEADER_LENGTH + resolver_name_length;
+            let address_offset = host_offset + host_length;
+            AgentEvent::NameResolution {
+                time_ns,
+                host: read_string(message, host_offset, host_length),
+                address: message
+                    .get(address_offset..address_offset + address_length)
+                    .and_then(read_ip)
+                    .map(|address| address.to_string()),
+            }
+        }
+        NAME_RESOLUTION_LOOKUP => {
+            let resolver_name_length = read_length(message, 16).unwrap_or_default();
+            let name_length = read_length(message, 20).unwrap_or_default();
+            let resolved_name_length = read_length(message, 24).unwrap_or_default();
+            let name_offset = LOOKUP_LOG_HEADER_LENGTH + resolver_name_length;
+            AgentEvent::NameResolution {
+                time_ns,
+                host: read_string(message, name_offset, name_length),
+                address: read_string(message, name_offset + name_length, resolved_name_length)
+                    .filter(|name| !name.is_empty()),
+            }
+        }
+        NAME_RESOLUTION_HOST_NAME => AgentEvent::NameResolution {
+            time_ns,
+            host: read_length(message, 16)
+                .and_then(|length| read_string(message, HOST_NAME_LOG_HEADER_LENGTH, length)),
+            address: None,
+        },
+        _ => match command_layout(msg_type_id) {
+            Some(layout) => {
+                let body = message.get(CMD_LOG_HEADER_LENGTH..).unwrap_or_default();
+                AgentEvent::Command {
+                    time_ns,
+                    correlation_id: layout
+                        .correlation_id
+                        .and_then(|offset| read_i64(body, offset)),
+                    stream_id: layout.stream_id.and_then(|offset| read_i32(body, offset)),
+                    channel: layout.channel_length.and_then(|offset| {
+                        read_string(body, offset + 4, read_length(body, offset)?)
+                    }),
+                }
+            }
+            None => AgentEvent::Other { time_ns },
+        },
+    }
+}
+
+fn decode_frame(time_ns: i64, message: &[u8]) -> AgentEvent {
+    let (Some(sockaddr_length), Some(frame_length)) =
+        (read_length(message, 8), read_i32(message, 12))
+    else {
+        return AgentEvent::Other { time_ns };
+    };
+    // The sender or receiver's address follows the header, then the start of the frame
+    let frame_offset = FRAME_LOG_HEADER_LENGTH + sockaddr_length;
+    let frame_type = message
+        .get(frame_offset + 6..frame_offset + 8)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]) as u32)
+        .unwrap_or(u32::MAX);
+    AgentEvent::Frame {
+        time_ns,
+        address: message
+            .get(FRAME_LOG_HEADER_LENGTH..frame_offset)
+            .and_then(read_sockaddr),
+        frame_type,
+        frame_length,
+    }
+}
+
+unsafe extern "C" fn on_event(
+    msg_type_id: i32,
+    buffer: *const c_void,
+    length: usize,
+    _clientd: *mut c_void,
+) {
+    let event = event_name(msg_type_id);
+    let message = slice::from_raw_parts(buffer as *const u8, length);
+    match decode_event(msg_type_id, message) {
+        AgentEvent::Frame {
+            time_ns,
+            address,
+            frame_type,
+            frame_length,
+        } => {
+            let address = address
+                .map(|address| address.to_string())
+                .unwrap_or_default();
+            tracing::trace!(
+                target: "aeron_driver_agent",
+                event = %event,
+                time_ns,
+                address = %address,
+                frame_type = frame_type_name(frame_type),
+                frame_length,
+            );
+        }
+        AgentEvent::Command {
+            time_ns,
+            correlation_id,
+            stream_id,
+            channel,
+        } => {
+            tracing::debug!(
+                target: "aeron_driver_agent",
+                event = %event,
+                time_ns,
+                correlation_id,
+                stream_id,
+                channel = channel.as_deref(),
+            );
+        }
+        AgentEvent::NameResolution {
+            time_ns,
+            host,
+            address,
+        } => {
+            tracing::debug!(
+                target: "aeron_driver_agent",
+                event = %event,
+                time_ns,
+                host = host.as_deref(),
+                address = address.as_deref(),
+            );
+        }
+        AgentEvent::Other { time_ns } => {
+            tracing::debug!(
+                target: "aeron_driver_agent",
+                event = %event,
+                time_ns,
+                length,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame_record(address: SocketAddr, frame: &[u8]) -> Vec<u8> {
+        let mut storage: libc::sockaddr_storage = unsafe { mem::zeroed() };
+        unsafe { sockaddr::write(address, &mut storage) };
+        let sockaddr_length = sockaddr::raw_len(&address);
+        let sockaddr_bytes =
+            unsafe { slice::from_raw_parts(&storage as *const _ as *const u8, sockaddr_length) };
+
+        let mut record = 1_234i64.to_le_bytes().to_vec();
+        record.extend_from_slice(&(sockaddr_length as i32).to_le_bytes());
+        record.extend_from_slice(&(frame.len() as i32).to_le_bytes());
+        record.extend_from_slice(sockaddr_bytes);
+        record.extend_from_slice(frame);
+        record
+    }
+
+    #[test]
+    fn decode_frame_record() {
+        let address: SocketAddr = "127.0.0.1:40123".parse().unwrap();
+        let mut frame = vec![0u8; 36];
+        frame[6..8].copy_from_slice(&(FRAME_TYPE_SM as u16).to_le_bytes());
+        let record = frame_record(address, &frame);
+
+        assert_eq!(
+            decode_event(FRAME_IN, &record),
+            AgentEvent::Frame {
+                time_ns: 1_234,
+                address: Some(address),
+                frame_type: FRAME_TYPE_SM,
+                frame_length: 36,
+            }
+        );
+        assert_eq!(frame_type_name(FRAME_TYPE_SM), "SM");
+    }
+
+    #[test]
+    fn decode_command_record() {
+        let channel = "aeron:udp?endpoint=localhost:40123";
+        let mut record = 2_345i64.to_le_bytes().to_vec();
+        record.extend_from_slice(&1i64.to_le_bytes());
+        record.extend_from_slice(&7i64.to_le_bytes());
+        record.extend_from_slice(&99i64.to_le_bytes());
+        record.extend_from_slice(&1001i32.to_le_bytes());
+        record.extend_from_slice(&(channel.len() as i32).to_le_bytes());
+        record.extend_from_slice(channel.as_bytes());
+
+        assert_eq!(
+            decode_event(CMD_IN_ADD_PUBLICATION, &record),
+            AgentEvent::Command {
+                time_ns: 2_345,
+                correlation_id: Some(99),
+                stream_id: Some(1001),
+                channel: Some(channel.to_string()),
+            }
+        );
+        assert_eq!(
+            decode_event(CMD_OUT_ON_CLIENT_TIMEOUT, &record[..24]),
+            AgentEvent::Command {
+                time_ns: 2_345,
+                correlation_id: None,
+                stream_id: None,
+                channel: None,
+            }
+        );
+    }
+
+    #[test]
+    fn decode_name_resolution_records() {
+        let mut record = 3_456i64.to_le_bytes().to_vec();
+        record.extend_from_slice(&10i64.to_le_bytes());
+        record.extend_from_slice(&7i32.to_le_bytes());
+        record.extend_from_slice(&9i32.to_le_bytes());
+        record.extend_from_slice(&4i32.to_le_bytes());
+        record.extend_from_slice(&[0; 4]);
+        record.extend_from_slice(b"default");
+        record.extend_from_slice(b"localhost");
+        record.extend_from_slice(&[127, 0, 0, 1]);
+        assert_eq!(
+            decode_event(NAME_RESOLUTION_RESOLVE, &record),
+            AgentEvent::NameResolution {
+                time_ns: 3_456,
+                host: Some("localhost".to_string()),
+                address: Some("127.0.0.1".to_string()),
+            }
+        );
+
+        let neighbor: SocketAddr = "10.0.0.2:8050".parse().unwrap();
+        let neighbor_record = frame_record(neighbor, &[]);
+        let mut record = 4_567i64.to_le_bytes().to_vec();
+        record.extend_from_slice(&neighbor_record[FRAME_LOG_HEADER_LENGTH..]);
+        assert_eq!(
+            decode_event(NAME_RESOLUTION_NEIGHBOR_ADDED, &record),
+            AgentEvent::NameResolution {
+                time_ns: 4_567,
+                host: None,
+                address: Some(neighbor.to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn decode_other_record() {
+        let mut record = 5_678i64.to_le_bytes().to_vec();
+        record.extend_from_slice(b"payload");
+        assert_eq!(
+            decode_event(100, &record),
+            AgentEvent::Other { time_ns: 5_678 }
+        );
+        assert_eq!(decode_event(100, &[1, 2]), AgentEvent::Other { time_ns: 0 });
+        assert_eq!(
+            decode_event(FRAME_OUT, &[1, 2]),
+            AgentEvent::Other { time_ns: 0 }
+        );
+    }
+}
@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::cnc::CncFile;
+use crate::counters::{read_counters, CounterEntry, SYSTEM_COUNTER_TYPE_ID};
+
+// System counter ids, mirroring `aeron_system_counters.h`. They are stored in the key of
+// each system counter.
+pub const CONDUCTOR_MAX_CYCLE_TIME_COUNTER_ID: i32 = 26;
+pub const CONDUCTOR_CYCLE_TIME_THRESHOLD_EXCEEDED_COUNTER_ID: i32 = 27;
+pub const SENDER_MAX_CYCLE_TIME_COUNTER_ID: i32 = 28;
+pub const SENDER_CYCLE_TIME_THRESHOLD_EXCEEDED_COUNTER_ID: i32 = 29;
+pub const RECEIVER_MAX_CYCLE_TIME_COUNTER_ID: i32 = 30;
+pub const RECEIVER_CYCLE_TIME_THRESHOLD_EXCEEDED_COUNTER_ID: i32 = 31;
+pub const NAME_RESOLVER_MAX_TIME_COUNTER_ID: i32 = 32;
+pub const NAME_RESOLVER_TIME_THRESHOLD_EXCEEDED_COUNTER_ID: i32 = 33;
+
+/// A driver agent whose duty cycle time is tracked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum AgentRole {
+    Conductor,
+    Sender,
+    Receiver,
+    NameResolver,
+}
+
+impl AgentRole {
+    pub const ALL: [AgentRole; 4] = [
+        AgentRole::Conductor,
+        AgentRole::Sender,
+        AgentRole::Receiver,
+        AgentRole::NameResolver,
+    ];
+
+    fn counter_ids(self) -> (i32, i32) {
+        match self {
+            AgentRole::Conductor => (
+                CONDUCTOR_MAX_CYCLE_TIME_COUNTER_ID,
+                CONDUCTOR_CYCLE_TIME_THRESHOLD_EXCEEDED_COUNTER_ID,
+            ),
+            AgentRole::Sender => (
+                SENDER_MAX_CYCLE_TIME_COUNTER_ID,
+                SENDER_CYCLE_TIME_THRESHOLD_EXCEEDED_COUNTER_ID,
+            ),
+            AgentRole::Receiver => (
+                RECEIVER_MAX_CYCLE_TIME_COUNTER_ID,
+                RECEIVER_CYCLE_TIME_THRESHOLD_EXCEEDED_COUNTER_ID,
+            ),
+            AgentRole::NameResolver => (
+                NAME_RESOLVER_MAX_TIME_COUNTER_ID,
+                NAME_RESOLVER_TIME_THRESHOLD_EXCEEDED_COUNTER_ID,
+            ),
+        }
+    }
+}
+
+impl fmt::Display for AgentRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            AgentRole::Conductor => "conductor",
+            AgentRole::Sender => "sender",
+            AgentRole::Receiver => "receiver",
+            AgentRole::NameResolver => "name resolver",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Duty cycle counters of one driver agent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DutyCycleStats {
+    pub role: AgentRole,
+    /// Longest duty cycle observed so far.
+    pub max_cycle_time_ns: i64,
+    /// Number of duty cycles that took longer than the configured threshold.
+    pub threshold_exceeded: i64,
+}
+
+fn system_counter_id(counter: &CounterEntry) -> Option<i32> {
+    if counter.type_id != SYSTEM_COUNTER_TYPE_ID {
+        return None;
+    }
+    Some(i32::from_le_bytes(counter.key.get(0..4)?.try_into().ok()?))
+}
+
+/// Extracts the duty cycle counters of each agent found in `counters`.
+pub fn duty_cycle_stats(counters: &[CounterEntry]) -> Vec<DutyCycleStats> {
+    let values: HashMap<i32, i64> = counters
+        .iter()
+        .filter_map(|counter| Some((system_counter_id(counter)?, counter.value)))
+        .collect();
+
+    AgentRole::ALL
+        .iter()
+        .filter_map(|&role| {
+            let (max_cycle_time_id, threshold_exceeded_id) = role.counter_ids();
+            Some(DutyCycleStats {
+                role,
+                max_cycle_time_ns: *values.get(&max_cycle_time_id)?,
+                threshold_exceeded: *values.get(&threshold_exceeded_id)?,
+            })
+        })
+        .collect()
+}
+
+pub fn read_duty_cycle_stats(cnc: &CncFile) -> Vec<DutyCycleStats> {
+    duty_cycle_stats(&read_counters(cnc))
+}
+
+/// Duty cycles of an agent that exceeded the threshold since the previous check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stall {
+    pub role: AgentRole,
+    pub count: i64,
+    pub max_cycle_time_ns: i64,
+}
+
+/// Reports stalls as they happen by comparing threshold exceeded counts between checks.
+#[derive(Debug, Default)]
+pub struct StallMonitor {
+    threshold_exceeded: HashMap<AgentRole, i64>,
+}
+
+impl StallMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the agents that stalled since the last call. The first call only records
+    /// the current counts.
+    pub fn check(&mut self, stats: &[DutyCycleStats]) -> Vec<Stall> {
+        let mut stalls = Vec::new();
+        for stat in stats {
+            let previous = self
+                .threshold_exceeded
+                .insert(stat.role, stat.threshold_exceeded);
+            if let Some(previous) = previous {
+                if stat.threshold_exceeded > previous {
+                    stalls.push(Stall {
+                        role: stat.role,
+                        count: stat.threshold_exceeded - previous,
+                        max_cycle_time_ns: stat.max_cycle_time_ns,
+                    });
+                }
+            }
+        }
+        stalls
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn system_counter(id: i32, value: i64) -> CounterEntry {
+        CounterEntry {
+            id,
+            type_id: SYSTEM_COUNTER_TYPE_ID,
+            value,
+            label: String::new(),
+            key: id.to_le_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn stalls_since_last_check() {
+        let counters = |sender_exceeded| {
+            vec![
+                system_counter(CONDUCTOR_MAX_CYCLE_TIME_COUNTER_ID, 1_000),
+                system_counter(CONDUCTOR_CYCLE_TIME_THRESHOLD_EXCEEDED_COUNTER_ID, 0),
+                system_counter(SENDER_MAX_CYCLE_TIME_COUNTER_ID, 5_000_000),
+                system_counter(
+                    SENDER_CYCLE_TIME_THRESHOLD_EXCEEDED_COUNTER_ID,
+                    sender_exceeded,
+                ),
+            ]
+        };
+
+        let stats = duty_cycle_stats(&counters(1));
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[1].role, AgentRole::Sender);
+
+        let mut monitor = StallMonitor::new();
+        assert!(monitor.check(&stats).is_empty());
+        assert_eq!(
+            monitor.check(&duty_cycle_stats(&counters(3))),
+            vec![Stall {
+                role: AgentRole::Sender,
+                count: 2,
+                max_cycle_time_ns: 5_000_000,
+            }]
+        );
+    }
+}
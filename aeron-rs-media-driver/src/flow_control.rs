@@ -0,0 +1,389 @@
+use libaeron_driver_sys as aeron_driver;
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use crate::common::CallbackRegistry;
+use crate::sockaddr;
+
+/// Status message flag asking the sender to send a setup frame.
+pub const STATUS_MESSAGE_SEND_SETUP_FLAG: u8 = 0x80;
+/// Status message flag set by a receiver that has seen the end of the stream.
+pub const STATUS_MESSAGE_END_OF_STREAM_FLAG: u8 = 0x40;
+
+const STATUS_MESSAGE_LENGTH: usize = 36;
+const STATUS_MESSAGE_WITH_GROUP_TAG_LENGTH: usize = 44;
+
+/// A status message sent by a receiver of a network publication.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusMessage {
+    pub flags: u8,
+    pub session_id: i32,
+    pub stream_id: i32,
+    pub consumption_term_id: i32,
+    pub consumption_term_offset: i32,
+    pub receiver_window: i32,
+    pub receiver_id: i64,
+    pub group_tag: Option<i64>,
+    /// Stream position the receiver has consumed up to.
+    pub position: i64,
+    pub receiver_address: Option<SocketAddr>,
+}
+
+impl StatusMessage {
+    /// Decodes a status message frame, computing its position from the publication's
+    /// `initial_term_id` and `position_bits_to_shift`.
+    pub fn decode(
+        frame: &[u8],
+        initial_term_id: i32,
+        position_bits_to_shift: usize,
+    ) -> Option<StatusMessage> {
+        if frame.len() < STATUS_MESSAGE_LENGTH {
+            return None;
+        }
+        let i32_at =
+            |offset: usize| i32::from_le_bytes(frame[offset..offset + 4].try_into().unwrap());
+        let i64_at =
+            |offset: usize| i64::from_le_bytes(frame[offset..offset + 8].try_into().unwrap());
+
+        let consumption_term_id = i32_at(16);
+        let consumption_term_offset = i32_at(20);
+        let position = ((consumption_term_id.wrapping_sub(initial_term_id) as i64)
+            << position_bits_to_shift)
+            + consumption_term_offset as i64;
+        let group_tag = if frame.len() >= STATUS_MESSAGE_WITH_GROUP_TAG_LENGTH {
+            Some(i64_at(36))
+        } else {
+            None
+        };
+
+        Some(StatusMessage {
+            flags: frame[5],
+            session_id: i32_at(8),
+            stream_id: i32_at(12),
+            consumption_term_id,
+            consumption_term_offset,
+            receiver_window: i32_at(24),
+            receiver_id: i64_at(28),
+            group_tag,
+            position,
+            receiver_address: None,
+        })
+    }
+
+    pub fn is_end_of_stream(&self) -> bool {
+        self.flags & STATUS_MESSAGE_END_OF_STREAM_FLAG != 0
+    }
+}
+
+/// The network publication a flow control strategy is created for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlowControlPublication {
+    pub stream_id: i32,
+    pub session_id: i32,
+    pub registration_id: i64,
+    pub initial_term_id: i32,
+    pub term_length: usize,
+}
+
+/// Decides how far a network publication's sender may advance, from the status messages
+/// of its receivers. Each method returns the new sender limit as a stream position.
+///
+/// Strategies run on the sender agent thread of the driver.
+pub trait FlowControlStrategy: Send {
+    fn on_status_message(&mut self, status: &StatusMessage, sender_limit: i64, now_ns: i64) -> i64;
+
+    fn on_idle(
+        &mut self,
+        _now_ns: i64,
+        sender_limit: i64,
+        _sender_position: i64,
+        _is_end_of_stream: bool,
+    ) -> i64 {
+        sender_limit
+    }
+
+    /// Called instead of `on_status_message` for status messages asking for a setup frame.
+    fn on_setup(&mut self, status: &StatusMessage, sender_limit: i64, now_ns: i64) -> i64 {
+        self.on_status_message(status, sender_limit, now_ns)
+    }
+
+    /// Whether enough receivers are connected for the publication to count as connected.
+    fn has_required_receivers(&self) -> bool {
+        true
+    }
+
+    /// Limits how much of a NAKed range is retransmitted at once.
+    fn max_retransmission_length(
+        &self,
+        term_offset: usize,
+        resend_length: usize,
+        term_length: usize,
+        _mtu_length: usize,
+    ) -> usize {
+        resend_length.min(term_length.saturating_sub(term_offset))
+    }
+}
+
+/// Creates a strategy for each network publication.
+pub type FlowControlSupplier =
+    dyn Fn(&FlowControlPublication) -> Box<dyn FlowControlStrategy> + Send + Sync;
+
+static UNICAST_SUPPLIERS: CallbackRegistry<FlowControlSupplier> = CallbackRegistry::new();
+static MULTICAST_SUPPLIERS: CallbackRegistry<FlowControlSupplier> = CallbackRegistry::new();
+
+struct StrategyState {
+    strategy: Box<dyn FlowControlStrategy>,
+}
+
+pub(crate) fn set_unicast_supplier(
+    context: *mut aeron_driver::aeron_driver_context_t,
+    supplier: Arc<FlowControlSupplier>,
+) -> i32 {
+    UNICAST_SUPPLIERS.insert(context, supplier);
+    unsafe {
+        aeron_driver::aeron_driver_context_set_unicast_flowcontrol_supplier(
+            context,
+            Some(unicast_supplier),
+        )
+    }
+}
+
+pub(crate) fn set_multicast_supplier(
+    context: *mut aeron_driver::aeron_driver_context_t,
+    supplier: Arc<FlowControlSupplier>,
+) -> i32 {
+    MULTICAST_SUPPLIERS.insert(context, supplier);
+    unsafe {
+        aeron_driver::aeron_driver_context_set_multicast_flowcontrol_supplier(
+            context,
+            Some(multicast_supplier),
+        )
+    }
+}
+
+pub(crate) fn remove_suppliers(context: *mut aeron_driver::aeron_driver_context_t) {
+    UNICAST_SUPPLIERS.remove(context);
+    MULTICAST_SUPPLIERS.remove(context);
+}
+
+#[allow(clippy::too_many_arguments)]
+unsafe extern "C" fn unicast_supplier(
+    strategy: *mut *mut aeron_driver::aeron_flow_control_strategy_t,
+    context: *mut aeron_driver::aeron_driver_context_t,
+    _channel: *const aeron_driver::aeron_udp_channel_t,
+    stream_id: i32,
+    session_id: i32,
+    registration_id: i64,
+    initial_term_id: i32,
+    term_length: usize,
+) -> i32 {
+    let publication = FlowControlPublication {
+        stream_id,
+        session_id,
+        registration_id,
+        initial_term_id,
+        term_length,
+    };
+    supply(&UNICAST_SUPPLIERS, strategy, context, &publication)
+}
+
+#[allow(clippy::too_many_arguments)]
+unsafe extern "C" fn multicast_supplier(
+    strategy: *mut *mut aeron_driver::aeron_flow_control_strategy_t,
+    context: *mut aeron_driver::aeron_driver_context_t,
+    _channel: *const aeron_driver::aeron_udp_channel_t,
+    stream_id: i32,
+    session_id: i32,
+    registration_id: i64,
+    initial_term_id: i32,
+    term_length: usize,
+) -> i32 {
+    let publication = FlowControlPublication {
+        stream_id,
+        session_id,
+        registration_id,
+        initial_term_id,
+        term_length,
+    };
+    supply(&MULTICAST_SUPPLIERS, strategy, context, &publication)
+}
+
+unsafe fn supply(
+    suppliers: &CallbackRegistry<FlowControlSupplier>,
+    strategy: *mut *mut aeron_driver::aeron_flow_control_strategy_t,
+    context: *mut aeron_driver::aeron_driver_context_t,
+    publication: &FlowControlPublication,
+) -> i32 {
+    let Some(supplier) = suppliers.get(context) else {
+        return -1;
+    };
+    *strategy = new_strategy(supplier(publication));
+    0
+}
+
+fn new_strategy(
+    strategy: Box<dyn FlowControlStrategy>,
+) -> *mut aeron_driver::aeron_flow_control_strategy_t {
+    let state = Box::new(StrategyState { strategy });
+    let mut raw: aeron_driver::aeron_flow_control_strategy_t = unsafe { std::mem::zeroed() };
+    raw.on_status_message = Some(on_status_message);
+    raw.on_idle = Some(on_idle);
+    raw.on_setup = Some(on_setup);
+    raw.on_error = Some(on_error);
+    raw.fini = Some(fini);
+    raw.has_required_receivers = Some(has_required_receivers);
+    raw.on_trigger_send_setup = Some(on_trigger_send_setup);
+    raw.max_retransmission_length = Some(max_retransmission_length);
+    raw.state = Box::into_raw(state) as *mut _;
+    Box::into_raw(Box::new(raw))
+}
+
+unsafe fn decode_status_message(
+    frame: *const u8,
+    length: usize,
+    receiver_address: *mut aeron_driver::sockaddr_storage,
+    initial_term_id: i32,
+    position_bits_to_shift: usize,
+) -> Option<StatusMessage> {
+    let frame = std::slice::from_raw_parts(frame, length);
+    let mut status = StatusMessage::decode(frame, initial_term_id, position_bits_to_shift)?;
+    status.receiver_address = sockaddr::from_raw(receiver_address);
+    Some(status)
+}
+
+#[allow(clippy::too_many_arguments)]
+unsafe extern "C" fn on_status_message(
+    state: *mut std::ffi::c_void,
+    sm: *const u8,
+    length: usize,
+    receiver_address: *mut aeron_driver::sockaddr_storage,
+    sender_limit: i64,
+    initial_term_id: i32,
+    position_bits_to_shift: usize,
+    now_ns: i64,
+) -> i64 {
+    let state = &mut *(state as *mut StrategyState);
+    match decode_status_message(
+        sm,
+        length,
+        receiver_address,
+        initial_term_id,
+        position_bits_to_shift,
+    ) {
+        Some(status) => state
+            .strategy
+            .on_status_message(&status, sender_limit, now_ns),
+        None => sender_limit,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+unsafe extern "C" fn on_setup(
+    state: *mut std::ffi::c_void,
+    sm: *const u8,
+    length: usize,
+    receiver_address: *mut aeron_driver::sockaddr_storage,
+    sender_limit: i64,
+    initial_term_id: i32,
+    position_bits_to_shift: usize,
+    now_ns: i64,
+) -> i64 {
+    let state = &mut *(state as *mut StrategyState);
+    match decode_status_message(
+        sm,
+        length,
+        receiver_address,
+        initial_term_id,
+        position_bits_to_shift,
+    ) {
+        Some(status) => state.strategy.on_setup(&status, sender_limit, now_ns),
+        None => sender_limit,
+    }
+}
+
+unsafe extern "C" fn on_idle(
+    state: *mut std::ffi::c_void,
+    now_ns: i64,
+    sender_limit: i64,
+    sender_position: i64,
+    is_end_of_stream: bool,
+) -> i64 {
+    let state = &mut *(state as *mut StrategyState);
+    state
+        .strategy
+        .on_idle(now_ns, sender_limit, sender_position, is_end_of_stream)
+}
+
+unsafe extern "C" fn on_error(
+    _state: *mut std::ffi::c_void,
+    _error: *const u8,
+    _length: usize,
+    _receiver_address: *mut aeron_driver::sockaddr_storage,
+    _now_ns: i64,
+) {
+}
+
+unsafe extern "C" fn on_trigger_send_setup(
+    _state: *mut std::ffi::c_void,
+    _sm: *const u8,
+    _length: usize,
+    _receiver_address: *mut aeron_driver::sockaddr_storage,
+    _now_ns: i64,
+) {
+}
+
+unsafe extern "C" fn has_required_receivers(
+    strategy: *mut aeron_driver::aeron_flow_control_strategy_t,
+) -> bool {
+    let state = &*((*strategy).state as *const StrategyState);
+    state.strategy.has_required_receivers()
+}
+
+unsafe extern "C" fn max_retransmission_length(
+    state: *mut std::ffi::c_void,
+    term_offset: usize,
+    resend_length: usize,
+    term_length: usize,
+    mtu_length: usize,
+) -> usize {
+    let state = &*(state as *const StrategyState);
+    state
+        .strategy
+        .max_retransmission_length(term_offset, resend_length, term_length, mtu_length)
+}
+
+unsafe extern "C" fn fini(strategy: *mut aeron_driver::aeron_flow_control_strategy_t) -> i32 {
+    let strategy = Box::from_raw(strategy);
+    drop(Box::from_raw(strategy.state as *mut StrategyState));
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_status_message_position() {
+        let mut frame = vec![0u8; STATUS_MESSAGE_LENGTH];
+        frame[0..4].copy_from_slice(&(STATUS_MESSAGE_LENGTH as i32).to_le_bytes());
+        frame[5] = STATUS_MESSAGE_END_OF_STREAM_FLAG;
+        frame[8..12].copy_from_slice(&7i32.to_le_bytes());
+        frame[12..16].copy_from_slice(&1001i32.to_le_bytes());
+        frame[16..20].copy_from_slice(&12i32.to_le_bytes());
+        frame[20..24].copy_from_slice(&256i32.to_le_bytes());
+        frame[24..28].copy_from_slice(&65536i32.to_le_bytes());
+        frame[28..36].copy_from_slice(&99i64.to_le_bytes());
+
+        let status = StatusMessage::decode(&frame, 10, 16).unwrap();
+        assert_eq!(status.session_id, 7);
+        assert_eq!(status.stream_id, 1001);
+        assert_eq!(status.receiver_window, 65536);
+        assert_eq!(status.receiver_id, 99);
+        assert_eq!(status.group_tag, None);
+        assert_eq!(status.position, (2 << 16) + 256);
+        assert!(status.is_end_of_stream());
+
+        assert_eq!(StatusMessage::decode(&frame[..20], 10, 16), None);
+    }
+}
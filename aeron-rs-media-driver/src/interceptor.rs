@@ -0,0 +1,373 @@
+use libaeron_driver_sys as aeron_driver;
+
+use std::collections::VecDeque;
+use std::io;
+use std::net::SocketAddr;
+use std::ptr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use crate::common;
+use crate::transport::{TransportAddresses, UdpChannelTransport, UdpChannelTransportBindings};
+
+/// Environment variable the driver's `loss` interceptor reads its parameters from when they
+/// have not been configured with `AeronContext::set_loss_interceptor`.
+pub const LOSS_ARGS_ENV_VAR: &str = "AERON_UDP_CHANNEL_TRANSPORT_BINDINGS_LOSS_ARGS";
+
+// Serialises configuring the interceptor's process-wide parameters
+static LOSS_PARAMS_LOCK: Mutex<()> = Mutex::new(());
+
+// Frame header types, used to build the mask of frames the loss interceptor may drop.
+pub const FRAME_TYPE_PAD: u32 = 0x00;
+pub const FRAME_TYPE_DATA: u32 = 0x01;
+pub const FRAME_TYPE_NAK: u32 = 0x02;
+pub const FRAME_TYPE_SM: u32 = 0x03;
+pub const FRAME_TYPE_ERR: u32 = 0x04;
+pub const FRAME_TYPE_SETUP: u32 = 0x05;
+pub const FRAME_TYPE_RTTM: u32 = 0x06;
+pub const FRAME_TYPE_RES: u32 = 0x07;
+
+/// Parameters of the driver's incoming `loss` interceptor, which drops received frames of
+/// the selected types at random with a fixed rate.
+///
+/// The C interceptor keeps a single set of parameters for the whole process, so every
+/// driver that enables it uses the parameters configured last, including drivers that are
+/// already running. Tests that need different loss settings must run in separate test
+/// binaries.
+///
+/// The interceptor drops frames of every stream and cannot delay them. Use `Impairment`
+/// with `ImpairedBindings` to lose or delay the frames of selected streams only.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LossInterceptor {
+    /// Probability in `0.0..=1.0` that a matching frame is dropped.
+    pub rate: f64,
+    /// Seed for the random number generator, fixed for reproducible runs.
+    pub seed: u64,
+    /// Bit mask of frame types that may be dropped, `1 << FRAME_TYPE_DATA` for data only.
+    pub frame_type_mask: u64,
+}
+
+impl LossInterceptor {
+    /// Drops data frames at `rate` using `seed`.
+    pub fn data_loss(rate: f64, seed: u64) -> Self {
+        Self {
+            rate,
+            seed,
+            frame_type_mask: 1 << FRAME_TYPE_DATA,
+        }
+    }
+
+    /// Also drops frames of `frame_type`, e.g. `FRAME_TYPE_SM` to lose status messages.
+    pub fn with_frame_type(mut self, frame_type: u32) -> Self {
+        self.frame_type_mask |= 1 << frame_type;
+        self
+    }
+
+    /// The parameters in the `key=value|key=value` form the interceptor parses from
+    /// `LOSS_ARGS_ENV_VAR`.
+    pub fn to_args(&self) -> String {
+        format!(
+            "rate={}|seed={}|recv-msg-mask=0x{:X}",
+            self.rate, self.seed, self.frame_type_mask
+        )
+    }
+}
+
+/// Hands the loss parameters straight to the C interceptor, without going through the
+/// process environment.
+pub(crate) fn configure_loss(loss: &LossInterceptor) -> common::Result<()> {
+    let _guard = LOSS_PARAMS_LOCK
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+
+    // The interceptor loads its parameters from the environment once per process, when the
+    // first transport using it starts. Run that load now so it cannot later overwrite the
+    // parameters configured below.
+    let mut state = ptr::null_mut();
+    let result = unsafe {
+        aeron_driver::aeron_udp_channel_interceptor_loss_init_incoming(
+            &mut state,
+            ptr::null_mut(),
+            aeron_driver::aeron_udp_channel_transport_affinity_en_AERON_UDP_CHANNEL_TRANSPORT_AFFINITY_RECEIVER,
+        )
+    };
+    if result < 0 {
+        return Err(format!("failed to initialise loss interceptor error code {result}").into());
+    }
+
+    let params = aeron_driver::aeron_udp_channel_interceptor_loss_params_t {
+        rate: loss.rate,
+        recv_msg_type_mask: loss.frame_type_mask as _,
+        seed: loss.seed as _,
+    };
+    let result = unsafe { aeron_driver::aeron_udp_channel_interceptor_loss_configure(&params) };
+    if result < 0 {
+        return Err(format!("failed to configure loss interceptor error code {result}").into());
+    }
+    Ok(())
+}
+
+/// Loss and delay applied to received datagrams by `ImpairedBindings`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Impairment {
+    /// Probability in `0.0..=1.0` that a matching frame is dropped.
+    pub loss_rate: f64,
+    /// Seed for the random number generator, fixed for reproducible runs.
+    pub seed: u64,
+    /// Bit mask of frame types that are affected, `1 << FRAME_TYPE_DATA` for data only.
+    pub frame_type_mask: u64,
+    /// Streams whose frames are affected, all streams when empty.
+    pub stream_ids: Vec<i32>,
+    /// How long matching frames that are not dropped are held back.
+    pub delay: Duration,
+}
+
+impl Impairment {
+    /// Drops data frames at `rate` using `seed`.
+    pub fn data_loss(rate: f64, seed: u64) -> Self {
+        Self {
+            loss_rate: rate,
+            seed,
+            frame_type_mask: 1 << FRAME_TYPE_DATA,
+            stream_ids: Vec::new(),
+            delay: Duration::ZERO,
+        }
+    }
+
+    /// Also affects frames of `frame_type`, e.g. `FRAME_TYPE_SM` for status messages.
+    pub fn with_frame_type(mut self, frame_type: u32) -> Self {
+        self.frame_type_mask |= 1 << frame_type;
+        self
+    }
+
+    /// Limits the impairment to `stream_id`, adding to any streams already selected.
+    pub fn with_stream_id(mut self, stream_id: i32) -> Self {
+        self.stream_ids.push(stream_id);
+        self
+    }
+
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    fn matches(&self, frame: &[u8]) -> bool {
+        let Some(frame_type) = frame.get(6..8) else {
+            return false;
+        };
+        let frame_type = u16::from_le_bytes([frame_type[0], frame_type[1]]) as u32;
+        if frame_type >= 64 || self.frame_type_mask & (1 << frame_type) == 0 {
+            return false;
+        }
+        if self.stream_ids.is_empty() {
+            return true;
+        }
+        frame_stream_id(frame_type, frame).is_some_and(|id| self.stream_ids.contains(&id))
+    }
+}
+
+fn frame_stream_id(frame_type: u32, frame: &[u8]) -> Option<i32> {
+    // Data, pad and setup headers carry a term offset before the session and stream ids
+    let offset = match frame_type {
+        FRAME_TYPE_PAD | FRAME_TYPE_DATA | FRAME_TYPE_SETUP => 16,
+        FRAME_TYPE_NAK | FRAME_TYPE_SM | FRAME_TYPE_ERR | FRAME_TYPE_RTTM => 12,
+        _ => return None,
+    };
+    let bytes = frame.get(offset..offset + 4)?;
+    Some(i32::from_le_bytes(bytes.try_into().ok()?))
+}
+
+/// Transport bindings that apply an `Impairment` to the datagrams received by the
+/// transports of other Rust bindings: `UdpSocketBindings` for real UDP sockets, or the
+/// bindings of a `SimulatedNetwork` host. Clones share the dropped datagram count.
+#[derive(Clone)]
+pub struct ImpairedBindings<B> {
+    inner: B,
+    impairment: Impairment,
+    dropped: Arc<AtomicU64>,
+}
+
+impl<B: UdpChannelTransportBindings> ImpairedBindings<B> {
+    pub fn new(inner: B, impairment: Impairment) -> Self {
+        Self {
+            inner,
+            impairment,
+            dropped: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Number of datagrams dropped by every transport of these bindings.
+    pub fn dropped_datagrams(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl<B: UdpChannelTransportBindings> UdpChannelTransportBindings for ImpairedBindings<B> {
+    fn init(&self, addresses: &TransportAddresses) -> io::Result<Box<dyn UdpChannelTransport>> {
+        Ok(Box::new(ImpairedTransport {
+            inner: self.inner.init(addresses)?,
+            impairment: self.impairment.clone(),
+            // xorshift must not start from zero
+            random: self.impairment.seed | 1,
+            held: VecDeque::new(),
+            dropped: Arc::clone(&self.dropped),
+        }))
+    }
+}
+
+struct ImpairedTransport {
+    inner: Box<dyn UdpChannelTransport>,
+    impairment: Impairment,
+    random: u64,
+    // Datagrams waiting for their release time, in arrival order
+    held: VecDeque<(Instant, Vec<u8>, SocketAddr)>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl ImpairedTransport {
+    // xorshift64*, as in the simulated network
+    fn next_f64(&mut self) -> f64 {
+        self.random ^= self.random >> 12;
+        self.random ^= self.random << 25;
+        self.random ^= self.random >> 27;
+        let value = self.random.wrapping_mul(0x2545_F491_4F6C_DD1D);
+        (value >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+impl UdpChannelTransport for ImpairedTransport {
+    fn sendmmsg(&mut self, destination: SocketAddr, datagrams: &[&[u8]]) -> io::Result<usize> {
+        self.inner.sendmmsg(destination, datagrams)
+    }
+
+    fn recvmmsg(
+        &mut self,
+        limit: usize,
+        on_datagram: &mut dyn FnMut(&[u8], SocketAddr),
+    ) -> io::Result<usize> {
+        let mut received = Vec::new();
+        self.inner.recvmmsg(limit, &mut |datagram, source| {
+            received.push((datagram.to_vec(), source))
+        })?;
+
+        let now = Instant::now();
+        for (datagram, source) in received {
+            if !self.impairment.matches(&datagram) {
+                self.held.push_back((now, datagram, source));
+            } else if self.next_f64() < self.impairment.loss_rate {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            } else {
+                self.held
+                    .push_back((now + self.impairment.delay, datagram, source));
+            }
+        }
+
+        let mut delivered = 0;
+        let mut index = 0;
+        while delivered < limit && index < self.held.len() {
+            if self.held[index].0 <= now {
+                let (_, datagram, source) = self.held.remove(index).unwrap();
+                on_datagram(&datagram, source);
+                delivered += 1;
+            } else {
+                index += 1;
+            }
+        }
+        Ok(delivered)
+    }
+
+    fn local_addr(&self) -> Option<SocketAddr> {
+        self.inner.local_addr()
+    }
+
+    fn so_rcvbuf(&self) -> usize {
+        self.inner.so_rcvbuf()
+    }
+
+    fn close(&mut self) {
+        self.inner.close()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulated_network::SimulatedNetwork;
+    use std::net::{IpAddr, Ipv4Addr};
+
+    #[test]
+    fn loss_args() {
+        let loss = LossInterceptor::data_loss(0.25, 42).with_frame_type(FRAME_TYPE_SETUP);
+        assert_eq!(loss.to_args(), "rate=0.25|seed=42|recv-msg-mask=0x22");
+    }
+
+    fn data_frame(stream_id: i32, value: u8) -> Vec<u8> {
+        let mut frame = vec![0u8; 33];
+        frame[0..4].copy_from_slice(&33i32.to_le_bytes());
+        frame[6..8].copy_from_slice(&(FRAME_TYPE_DATA as u16).to_le_bytes());
+        frame[16..20].copy_from_slice(&stream_id.to_le_bytes());
+        frame[32] = value;
+        frame
+    }
+
+    fn addresses(bind: Option<SocketAddr>) -> TransportAddresses {
+        TransportAddresses {
+            bind,
+            connect: None,
+            multicast_interface: None,
+        }
+    }
+
+    fn receive_all(transport: &mut Box<dyn UdpChannelTransport>) -> Vec<u8> {
+        let mut values = Vec::new();
+        transport
+            .recvmmsg(usize::MAX, &mut |datagram, _| values.push(datagram[32]))
+            .unwrap();
+        values
+    }
+
+    #[test]
+    fn impairs_only_selected_streams() {
+        let network = SimulatedNetwork::new();
+        let host = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+        let address = SocketAddr::new(host, 40123);
+        let bindings = ImpairedBindings::new(
+            network.bindings(host),
+            Impairment::data_loss(1.0, 3).with_stream_id(1001),
+        );
+        let mut receiver = bindings.init(&addresses(Some(address))).unwrap();
+        let mut sender = network.bindings(host).init(&addresses(None)).unwrap();
+
+        for value in 0..4 {
+            let stream_id = if value % 2 == 0 { 1001 } else { 1002 };
+            sender
+                .sendmmsg(address, &[&data_frame(stream_id, value)])
+                .unwrap();
+        }
+        assert_eq!(receive_all(&mut receiver), vec![1, 3]);
+        assert_eq!(bindings.dropped_datagrams(), 2);
+    }
+
+    #[test]
+    fn delays_matching_frames() {
+        let network = SimulatedNetwork::new();
+        let host = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+        let address = SocketAddr::new(host, 40124);
+        let bindings = ImpairedBindings::new(
+            network.bindings(host),
+            Impairment::data_loss(0.0, 3)
+                .with_stream_id(1001)
+                .with_delay(Duration::from_millis(50)),
+        );
+        let mut receiver = bindings.init(&addresses(Some(address))).unwrap();
+        let mut sender = network.bindings(host).init(&addresses(None)).unwrap();
+
+        sender.sendmmsg(address, &[&data_frame(1001, 0)]).unwrap();
+        sender.sendmmsg(address, &[&data_frame(1002, 1)]).unwrap();
+        assert_eq!(receive_all(&mut receiver), vec![1]);
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert_eq!(receive_all(&mut receiver), vec![0]);
+    }
+}
@@ -1,4 +1,27 @@
 pub(crate) mod common;
 
 pub use common::*;
+pub mod agent_thread;
+pub mod backlog;
+pub mod client;
+pub mod cnc;
+pub mod codec;
+pub mod congestion_control;
+pub mod counters;
+#[cfg(feature = "driver-agent")]
+pub mod driver_agent;
+pub mod duty_cycle;
+pub mod flow_control;
+pub mod interceptor;
+pub mod loss_report;
 pub mod media_driver;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod name_resolver;
+pub mod rate_reporter;
+pub mod simulated_network;
+mod sockaddr;
+pub mod streams;
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_support;
+pub mod transport;
@@ -0,0 +1,152 @@
+use libaeron_sys as aeron;
+
+use std::collections::HashMap;
+use std::ffi::c_void;
+use std::os::raw::c_char;
+use std::slice;
+
+use serde::{Deserialize, Serialize};
+
+use crate::client::aeron_errmsg;
+use crate::cnc::CncFile;
+use crate::common;
+
+/// Loss observed by the driver on one image, as recorded in `loss-report.dat`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LossReportEntry {
+    pub observation_count: i64,
+    pub total_bytes_lost: i64,
+    pub first_observation_timestamp_ms: i64,
+    pub last_observation_timestamp_ms: i64,
+    pub session_id: i32,
+    pub stream_id: i32,
+    pub channel: String,
+    pub source: String,
+}
+
+impl LossReportEntry {
+    fn image_key(&self) -> (i32, i32, &str, &str) {
+        (
+            self.session_id,
+            self.stream_id,
+            self.channel.as_str(),
+            self.source.as_str(),
+        )
+    }
+}
+
+unsafe fn lossy_string(value: *const c_char, length: i32) -> String {
+    if value.is_null() || length <= 0 {
+        return String::new();
+    }
+    String::from_utf8_lossy(slice::from_raw_parts(value as *const u8, length as usize)).into_owned()
+}
+
+#[allow(clippy::too_many_arguments)]
+unsafe extern "C" fn on_loss_entry(
+    clientd: *mut c_void,
+    observation_count: i64,
+    total_bytes_lost: i64,
+    first_observation_timestamp: i64,
+    last_observation_timestamp: i64,
+    session_id: i32,
+    stream_id: i32,
+    channel: *const c_char,
+    channel_length: i32,
+    source: *const c_char,
+    source_length: i32,
+) {
+    let entries = &mut *(clientd as *mut Vec<LossReportEntry>);
+    entries.push(LossReportEntry {
+        observation_count,
+        total_bytes_lost,
+        first_observation_timestamp_ms: first_observation_timestamp,
+        last_observation_timestamp_ms: last_observation_timestamp,
+        session_id,
+        stream_id,
+        channel: lossy_string(channel, channel_length),
+        source: lossy_string(source, source_length),
+    });
+}
+
+/// Reads every entry of the driver's loss report.
+pub fn read_loss_report(cnc: &CncFile) -> common::Result<Vec<LossReportEntry>> {
+    let mut entries = Vec::new();
+    let result = unsafe {
+        aeron::aeron_cnc_loss_reporter_read(
+            cnc.get(),
+            Some(on_loss_entry),
+            &mut entries as *mut Vec<LossReportEntry> as *mut c_void,
+        )
+    };
+    if result < 0 {
+        return Err(format!(
+            "failed to read loss report error code {result}: {}",
+            aeron_errmsg()
+        )
+        .into());
+    }
+    Ok(entries)
+}
+
+/// Returns the loss observed in `current` since `baseline` was taken.
+///
+/// Images missing from the baseline are reported in full; images with no new loss are
+/// dropped. Counts and byte totals of the returned entries are deltas.
+pub fn diff_loss_reports(
+    baseline: &[LossReportEntry],
+    current: &[LossReportEntry],
+) -> Vec<LossReportEntry> {
+    let baseline: HashMap<_, _> = baseline
+        .iter()
+        .map(|entry| (entry.image_key(), entry))
+        .collect();
+
+    current
+        .iter()
+        .filter_map(|entry| match baseline.get(&entry.image_key()) {
+            None => Some(entry.clone()),
+            Some(previous) if entry.observation_count > previous.observation_count => {
+                Some(LossReportEntry {
+                    observation_count: entry.observation_count - previous.observation_count,
+                    total_bytes_lost: entry.total_bytes_lost - previous.total_bytes_lost,
+                    first_observation_timestamp_ms: previous.last_observation_timestamp_ms,
+                    ..entry.clone()
+                })
+            }
+            Some(_) => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(session_id: i32, observation_count: i64, total_bytes_lost: i64) -> LossReportEntry {
+        LossReportEntry {
+            observation_count,
+            total_bytes_lost,
+            first_observation_timestamp_ms: 100,
+            last_observation_timestamp_ms: 100 + observation_count,
+            session_id,
+            stream_id: 1001,
+            channel: "aeron:udp?endpoint=localhost:20121".to_string(),
+            source: "127.0.0.1:45678".to_string(),
+        }
+    }
+
+    #[test]
+    fn diff_reports_only_new_loss() {
+        let baseline = vec![entry(1, 2, 4096), entry(2, 1, 1024)];
+        let current = vec![entry(1, 5, 10240), entry(2, 1, 1024), entry(3, 1, 512)];
+
+        let diff = diff_loss_reports(&baseline, &current);
+        assert_eq!(diff.len(), 2);
+        assert_eq!(diff[0].session_id, 1);
+        assert_eq!(diff[0].observation_count, 3);
+        assert_eq!(diff[0].total_bytes_lost, 6144);
+        assert_eq!(diff[0].first_observation_timestamp_ms, 102);
+        assert_eq!(diff[1], entry(3, 1, 512));
+    }
+}
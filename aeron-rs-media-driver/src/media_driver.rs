@@ -1,9 +1,21 @@
 use libaeron_driver_sys as aeron_driver;
 
-use std::ffi::CStr;
+use std::ffi::{c_char, CStr, CString};
+use std::sync::Arc;
 
+use crate::agent_thread;
+use crate::agent_thread::AgentAffinity;
 use crate::common;
 use crate::common::ManagedCResource;
+use crate::congestion_control;
+use crate::congestion_control::{CongestionControlImage, CongestionControlStrategy};
+use crate::flow_control;
+use crate::flow_control::{FlowControlPublication, FlowControlStrategy};
+use crate::interceptor::{configure_loss, LossInterceptor};
+use crate::name_resolver;
+use crate::name_resolver::{NameResolver, ResolverCache};
+use crate::transport;
+use crate::transport::UdpChannelTransportBindings;
 use libaeron_driver_sys::aeron_driver_context_t;
 
 pub struct AeronContext {
@@ -23,6 +35,354 @@ impl AeronContext {
         Ok(Self { resource })
     }
 
+    pub fn get_dir(&self) -> String {
+        unsafe {
+            CStr::from_ptr(aeron_driver::aeron_driver_context_get_dir(
+                self.resource.get(),
+            ))
+        }
+        .to_string_lossy()
+        .into_owned()
+    }
+
+    pub fn set_dir(&self, dir: &str) -> common::Result<()> {
+        let dir = CString::new(dir)?;
+        let result = unsafe {
+            aeron_driver::aeron_driver_context_set_dir(self.resource.get(), dir.as_ptr())
+        };
+        check_set_result(result, "dir")
+    }
+
+    pub fn set_dir_delete_on_start(&self, value: bool) -> common::Result<()> {
+        let result = unsafe {
+            aeron_driver::aeron_driver_context_set_dir_delete_on_start(self.resource.get(), value)
+        };
+        check_set_result(result, "dir_delete_on_start")
+    }
+
+    pub fn set_dir_delete_on_shutdown(&self, value: bool) -> common::Result<()> {
+        let result = unsafe {
+            aeron_driver::aeron_driver_context_set_dir_delete_on_shutdown(
+                self.resource.get(),
+                value,
+            )
+        };
+        check_set_result(result, "dir_delete_on_shutdown")
+    }
+
+    pub fn set_threading_mode(
+        &self,
+        mode: aeron_driver::aeron_threading_mode_t,
+    ) -> common::Result<()> {
+        let result = unsafe {
+            aeron_driver::aeron_driver_context_set_threading_mode(self.resource.get(), mode)
+        };
+        check_set_result(result, "threading_mode")
+    }
+
+    pub fn set_term_buffer_length(&self, value: usize) -> common::Result<()> {
+        let result = unsafe {
+            aeron_driver::aeron_driver_context_set_term_buffer_length(self.resource.get(), value)
+        };
+        check_set_result(result, "term_buffer_length")
+    }
+
+    pub fn set_ipc_term_buffer_length(&self, value: usize) -> common::Result<()> {
+        let result = unsafe {
+            aeron_driver::aeron_driver_context_set_ipc_term_buffer_length(
+                self.resource.get(),
+                value,
+            )
+        };
+        check_set_result(result, "ipc_term_buffer_length")
+    }
+
+    pub fn set_client_liveness_timeout_ns(&self, value: u64) -> common::Result<()> {
+        let result = unsafe {
+            aeron_driver::aeron_driver_context_set_client_liveness_timeout_ns(
+                self.resource.get(),
+                value,
+            )
+        };
+        check_set_result(result, "client_liveness_timeout_ns")
+    }
+
+    pub fn set_image_liveness_timeout_ns(&self, value: u64) -> common::Result<()> {
+        let result = unsafe {
+            aeron_driver::aeron_driver_context_set_image_liveness_timeout_ns(
+                self.resource.get(),
+                value,
+            )
+        };
+        check_set_result(result, "image_liveness_timeout_ns")
+    }
+
+    pub fn set_publication_linger_timeout_ns(&self, value: u64) -> common::Result<()> {
+        let result = unsafe {
+            aeron_driver::aeron_driver_context_set_publication_linger_timeout_ns(
+                self.resource.get(),
+                value,
+            )
+        };
+        check_set_result(result, "publication_linger_timeout_ns")
+    }
+
+    pub fn set_timer_interval_ns(&self, value: u64) -> common::Result<()> {
+        let result = unsafe {
+            aeron_driver::aeron_driver_context_set_timer_interval_ns(self.resource.get(), value)
+        };
+        check_set_result(result, "timer_interval_ns")
+    }
+
+    pub fn set_term_buffer_sparse_file(&self, value: bool) -> common::Result<()> {
+        let result = unsafe {
+            aeron_driver::aeron_driver_context_set_term_buffer_sparse_file(
+                self.resource.get(),
+                value,
+            )
+        };
+        check_set_result(result, "term_buffer_sparse_file")
+    }
+
+    /// Sets the duty cycle time above which the conductor counts a cycle as exceeding its
+    /// threshold.
+    pub fn set_conductor_cycle_threshold_ns(&self, value: u64) -> common::Result<()> {
+        let result = unsafe {
+            aeron_driver::aeron_driver_context_set_conductor_cycle_threshold_ns(
+                self.resource.get(),
+                value,
+            )
+        };
+        check_set_result(result, "conductor_cycle_threshold_ns")
+    }
+
+    pub fn set_sender_cycle_threshold_ns(&self, value: u64) -> common::Result<()> {
+        let result = unsafe {
+            aeron_driver::aeron_driver_context_set_sender_cycle_threshold_ns(
+                self.resource.get(),
+                value,
+            )
+        };
+        check_set_result(result, "sender_cycle_threshold_ns")
+    }
+
+    pub fn set_receiver_cycle_threshold_ns(&self, value: u64) -> common::Result<()> {
+        let result = unsafe {
+            aeron_driver::aeron_driver_context_set_receiver_cycle_threshold_ns(
+                self.resource.get(),
+                value,
+            )
+        };
+        check_set_result(result, "receiver_cycle_threshold_ns")
+    }
+
+    /// Sets the name resolution time above which a resolution counts as exceeding its
+    /// threshold.
+    pub fn set_name_resolver_threshold_ns(&self, value: u64) -> common::Result<()> {
+        let result = unsafe {
+            aeron_driver::aeron_driver_context_set_name_resolver_threshold_ns(
+                self.resource.get(),
+                value,
+            )
+        };
+        check_set_result(result, "name_resolver_threshold_ns")
+    }
+
+    /// Adds the comma separated interceptors applied to received UDP frames, e.g. `loss`,
+    /// in front of those added before.
+    pub fn set_udp_channel_incoming_interceptors(&self, interceptors: &str) -> common::Result<()> {
+        let interceptors = CString::new(interceptors)?;
+        let result = unsafe {
+            let context = self.resource.get();
+            let bindings = aeron_driver::aeron_udp_channel_interceptor_bindings_load(
+                aeron_driver::aeron_driver_context_get_udp_channel_incoming_interceptors(context),
+                interceptors.as_ptr(),
+            );
+            if bindings.is_null() {
+                -1
+            } else {
+                aeron_driver::aeron_driver_context_set_udp_channel_incoming_interceptors(
+                    context, bindings,
+                )
+            }
+        };
+        check_set_result(result, "udp_channel_incoming_interceptors")
+    }
+
+    /// Adds the comma separated interceptors applied to sent UDP frames, in front of those
+    /// added before.
+    pub fn set_udp_channel_outgoing_interceptors(&self, interceptors: &str) -> common::Result<()> {
+        let interceptors = CString::new(interceptors)?;
+        let result = unsafe {
+            let context = self.resource.get();
+            let bindings = aeron_driver::aeron_udp_channel_interceptor_bindings_load(
+                aeron_driver::aeron_driver_context_get_udp_channel_outgoing_interceptors(context),
+                interceptors.as_ptr(),
+            );
+            if bindings.is_null() {
+                -1
+            } else {
+                aeron_driver::aeron_driver_context_set_udp_channel_outgoing_interceptors(
+                    context, bindings,
+                )
+            }
+        };
+        check_set_result(result, "udp_channel_outgoing_interceptors")
+    }
+
+    /// Replaces the driver's UDP sockets with transports created by `bindings`, e.g. the
+    /// bindings of a `SimulatedNetwork` host.
+    pub fn set_udp_channel_transport_bindings(
+        &self,
+        bindings: impl UdpChannelTransportBindings + 'static,
+    ) -> common::Result<()> {
+        let result = transport::set_bindings(self.resource.get(), Arc::new(bindings));
+        check_set_result(result, "udp_channel_transport_bindings")
+    }
+
+    /// Enables the C driver agent's event log for `events`, a comma separated list of event
+    /// names such as `FRAME_IN,FRAME_OUT`, `admin` or `all`, or a hex mask.
+    ///
+    /// The agent's event selection is process-wide. Read the events with
+    /// `driver_agent::EventLogForwarder`. Fails when `AERON_EVENT_LOG` is set, since the
+    /// agent then drains the events to its own log instead.
+    #[cfg(feature = "driver-agent")]
+    pub fn set_event_log(&self, events: &str) -> common::Result<()> {
+        if std::env::var_os("AERON_EVENT_LOG").is_some() {
+            return Err("AERON_EVENT_LOG is set, so the driver agent reads its own events".into());
+        }
+        let events = CString::new(events)?;
+
+        // The agent's one-time init applies AERON_EVENT_LOG and would clear any events
+        // enabled before it, so it is run first, with none enabled, which installs nothing.
+        static AGENT_INIT: std::sync::Once = std::sync::Once::new();
+        AGENT_INIT.call_once(|| unsafe {
+            aeron_driver::aeron_driver_agent_context_init(self.resource.get());
+        });
+
+        if !unsafe {
+            aeron_driver::aeron_driver_agent_logging_events_init(events.as_ptr(), std::ptr::null())
+        } {
+            return Err(format!("failed to set aeron context event log {events:?}").into());
+        }
+
+        let result = unsafe { aeron_driver::aeron_driver_agent_logging_ring_buffer_init() };
+        check_set_result(result, "event_log ring buffer")?;
+        let result = unsafe { aeron_driver::aeron_driver_agent_context_init(self.resource.get()) };
+        check_set_result(result, "event_log")
+    }
+
+    /// Enables the `loss` interceptor on received frames with the given parameters.
+    ///
+    /// The C interceptor holds one set of parameters for the whole process, so every driver
+    /// that enables it uses the parameters set last, including drivers already running.
+    /// Set them before starting the drivers, as receivers read them without synchronisation.
+    pub fn set_loss_interceptor(&self, loss: &LossInterceptor) -> common::Result<()> {
+        configure_loss(loss)?;
+        self.set_udp_channel_incoming_interceptors("loss")
+    }
+
+    /// Creates the flow control strategy of each unicast network publication with `supplier`.
+    pub fn set_unicast_flow_control(
+        &self,
+        supplier: impl Fn(&FlowControlPublication) -> Box<dyn FlowControlStrategy>
+            + Send
+            + Sync
+            + 'static,
+    ) -> common::Result<()> {
+        let result = flow_control::set_unicast_supplier(self.resource.get(), Arc::new(supplier));
+        check_set_result(result, "unicast_flowcontrol_supplier")
+    }
+
+    /// Creates the flow control strategy of each multicast network publication with `supplier`.
+    pub fn set_multicast_flow_control(
+        &self,
+        supplier: impl Fn(&FlowControlPublication) -> Box<dyn FlowControlStrategy>
+            + Send
+            + Sync
+            + 'static,
+    ) -> common::Result<()> {
+        let result = flow_control::set_multicast_supplier(self.resource.get(), Arc::new(supplier));
+        check_set_result(result, "multicast_flowcontrol_supplier")
+    }
+
+    /// Creates the congestion control strategy of each network image with `supplier`.
+    pub fn set_congestion_control(
+        &self,
+        supplier: impl Fn(&CongestionControlImage) -> Box<dyn CongestionControlStrategy>
+            + Send
+            + Sync
+            + 'static,
+    ) -> common::Result<()> {
+        let result = congestion_control::set_supplier(self.resource.get(), Arc::new(supplier));
+        check_set_result(result, "congestioncontrol_supplier")
+    }
+
+    /// Sets the name this driver is known by to the other drivers' name resolvers.
+    pub fn set_resolver_name(&self, name: &str) -> common::Result<()> {
+        let name = CString::new(name)?;
+        let result = unsafe {
+            aeron_driver::aeron_driver_context_set_resolver_name(self.resource.get(), name.as_ptr())
+        };
+        check_set_result(result, "resolver_name")
+    }
+
+    /// Sets the `host:port` the driver name resolver binds to, which enables it.
+    pub fn set_resolver_interface(&self, interface: &str) -> common::Result<()> {
+        let interface = CString::new(interface)?;
+        let result = unsafe {
+            aeron_driver::aeron_driver_context_set_resolver_interface(
+                self.resource.get(),
+                interface.as_ptr(),
+            )
+        };
+        check_set_result(result, "resolver_interface")
+    }
+
+    /// Sets the `host:port` of a driver to contact first when joining the resolver gossip.
+    pub fn set_resolver_bootstrap_neighbor(&self, neighbor: &str) -> common::Result<()> {
+        let neighbor = CString::new(neighbor)?;
+        let result = unsafe {
+            aeron_driver::aeron_driver_context_set_resolver_bootstrap_neighbor(
+                self.resource.get(),
+                neighbor.as_ptr(),
+            )
+        };
+        check_set_result(result, "resolver_bootstrap_neighbor")
+    }
+
+    /// Keeps `cache` filled with the names known to the driver name resolver, by
+    /// intercepting the resolutions it sends to its neighbors. Set it once per context.
+    pub fn set_resolver_cache(&self, cache: &ResolverCache) -> common::Result<()> {
+        let result = name_resolver::set_cache(self.resource.get(), cache.clone());
+        check_set_result(result, "resolver_cache")
+    }
+
+    /// Resolves channel endpoint names with the resolver created by `supplier`, e.g. a
+    /// `StaticNameResolver`.
+    pub fn set_name_resolver(
+        &self,
+        supplier: impl Fn() -> Box<dyn NameResolver> + Send + Sync + 'static,
+    ) -> common::Result<()> {
+        let result = name_resolver::set_supplier(self.resource.get(), Arc::new(supplier));
+        check_set_result(result, "name_resolver_supplier")
+    }
+
+    /// Runs `on_start` on each agent thread the driver starts, with the agent's role name,
+    /// e.g. to pin the thread to a core, raise its priority or name it.
+    pub fn set_agent_on_start(
+        &self,
+        on_start: impl Fn(&str) + Send + Sync + 'static,
+    ) -> common::Result<()> {
+        let result = agent_thread::set_on_start(self.resource.get(), Arc::new(on_start));
+        check_set_result(result, "agent_on_start_function")
+    }
+
+    /// Pins and names the driver's agent threads as they start.
+    pub fn set_agent_affinity(&self, affinity: AgentAffinity) -> common::Result<()> {
+        self.set_agent_on_start(move |role_name| affinity.on_start(role_name))
+    }
+
     // Add methods specific to AeronContext
     pub fn print_config(&self) -> common::Result<(), Box<dyn std::error::Error>> {
         print_aeron_config(self.resource.get())?;
@@ -30,6 +390,17 @@ impl AeronContext {
     }
 }
 
+impl Drop for AeronContext {
+    fn drop(&mut self) {
+        agent_thread::remove_on_start(self.resource.get());
+        flow_control::remove_suppliers(self.resource.get());
+        congestion_control::remove_supplier(self.resource.get());
+        name_resolver::remove_supplier(self.resource.get());
+        name_resolver::remove_cache(self.resource.get());
+        transport::remove_bindings(self.resource.get());
+    }
+}
+
 pub struct AeronDriver {
     resource: ManagedCResource<aeron_driver::aeron_driver_t>,
 }
@@ -63,6 +434,22 @@ impl AeronDriver {
     }
 }
 
+fn check_set_result(result: i32, name: &str) -> common::Result<()> {
+    if result < 0 {
+        return Err(format!("failed to set aeron context {name} error code {result}").into());
+    }
+    Ok(())
+}
+
+/// Reads a context string setting, which is null when it has not been set.
+unsafe fn optional_c_str<'a>(value: *const c_char) -> Option<&'a CStr> {
+    if value.is_null() {
+        None
+    } else {
+        Some(CStr::from_ptr(value))
+    }
+}
+
 fn threading_mode_to_str(mode: aeron_driver::aeron_threading_mode_t) -> &'static str {
     match mode {
         aeron_driver::aeron_threading_mode_enum::AERON_THREADING_MODE_DEDICATED => "DEDICATED",
@@ -366,6 +753,54 @@ fn print_aeron_config(context: *mut aeron_driver::aeron_driver_context_t) -> com
                 aeron_driver::aeron_driver_context_get_shared_idle_strategy_init_args(context)
             }),
         ),
+        (
+            "conductor_cycle_threshold_ns",
+            format!("{:?}", unsafe {
+                aeron_driver::aeron_driver_context_get_conductor_cycle_threshold_ns(context)
+            }),
+        ),
+        (
+            "sender_cycle_threshold_ns",
+            format!("{:?}", unsafe {
+                aeron_driver::aeron_driver_context_get_sender_cycle_threshold_ns(context)
+            }),
+        ),
+        (
+            "receiver_cycle_threshold_ns",
+            format!("{:?}", unsafe {
+                aeron_driver::aeron_driver_context_get_receiver_cycle_threshold_ns(context)
+            }),
+        ),
+        (
+            "name_resolver_threshold_ns",
+            format!("{:?}", unsafe {
+                aeron_driver::aeron_driver_context_get_name_resolver_threshold_ns(context)
+            }),
+        ),
+        (
+            "resolver_name",
+            format!("{:?}", unsafe {
+                optional_c_str(aeron_driver::aeron_driver_context_get_resolver_name(
+                    context,
+                ))
+            }),
+        ),
+        (
+            "resolver_interface",
+            format!("{:?}", unsafe {
+                optional_c_str(aeron_driver::aeron_driver_context_get_resolver_interface(
+                    context,
+                ))
+            }),
+        ),
+        (
+            "resolver_bootstrap_neighbor",
+            format!("{:?}", unsafe {
+                optional_c_str(
+                    aeron_driver::aeron_driver_context_get_resolver_bootstrap_neighbor(context),
+                )
+            }),
+        ),
     ];
 
     // Find the maximum length of the keys
@@ -0,0 +1,380 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use tiny_http::{Header, Response, Server};
+
+use crate::cnc::CncFile;
+use crate::common;
+use crate::counters::{
+    read_counters, CounterEntry, CLIENT_HEARTBEAT_TIMESTAMP_TYPE_ID, PER_IMAGE_TYPE_ID,
+    PUBLISHER_LIMIT_TYPE_ID, PUBLISHER_POSITION_TYPE_ID, RECEIVER_HWM_TYPE_ID,
+    RECEIVER_POSITION_TYPE_ID, RECEIVE_CHANNEL_STATUS_TYPE_ID, SENDER_BPE_TYPE_ID,
+    SENDER_LIMIT_TYPE_ID, SENDER_POSITION_TYPE_ID, SEND_CHANNEL_STATUS_TYPE_ID,
+    SUBSCRIBER_POSITION_TYPE_ID, SYSTEM_COUNTER_TYPE_ID,
+};
+
+/// Metric name for a counter type, `None` for types without a fixed name.
+fn type_metric_name(type_id: i32) -> Option<&'static str> {
+    match type_id {
+        PUBLISHER_LIMIT_TYPE_ID => Some("aeron_publisher_limit"),
+        SENDER_POSITION_TYPE_ID => Some("aeron_sender_position"),
+        RECEIVER_HWM_TYPE_ID => Some("aeron_receiver_hwm"),
+        SUBSCRIBER_POSITION_TYPE_ID => Some("aeron_subscriber_position"),
+        RECEIVER_POSITION_TYPE_ID => Some("aeron_receiver_position"),
+        SEND_CHANNEL_STATUS_TYPE_ID => Some("aeron_send_channel_status"),
+        RECEIVE_CHANNEL_STATUS_TYPE_ID => Some("aeron_receive_channel_status"),
+        SENDER_LIMIT_TYPE_ID => Some("aeron_sender_limit"),
+        PER_IMAGE_TYPE_ID => Some("aeron_per_image"),
+        CLIENT_HEARTBEAT_TIMESTAMP_TYPE_ID => Some("aeron_client_heartbeat_timestamp"),
+        PUBLISHER_POSITION_TYPE_ID => Some("aeron_publisher_position"),
+        SENDER_BPE_TYPE_ID => Some("aeron_sender_bpe"),
+        _ => None,
+    }
+}
+
+/// System counters that report a current level or a maximum rather than a running total.
+const SYSTEM_GAUGE_LABELS: &[&str] = &[
+    "ControllableIdleStrategy status",
+    "Conductor max cycle time",
+    "Sender max cycle time",
+    "Receiver max cycle time",
+    "NameResolver max time",
+    "Aeron software",
+    "Bytes currently mapped",
+];
+
+/// Prometheus metric type of a family of samples.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MetricType {
+    Counter,
+    Gauge,
+}
+
+impl MetricType {
+    fn as_str(self) -> &'static str {
+        match self {
+            MetricType::Counter => "counter",
+            MetricType::Gauge => "gauge",
+        }
+    }
+}
+
+/// System counters only ever grow, apart from the few levels and maxima listed above.
+fn system_metric_type(label: &str) -> MetricType {
+    if SYSTEM_GAUGE_LABELS
+        .iter()
+        .any(|gauge| label.starts_with(gauge))
+    {
+        MetricType::Gauge
+    } else {
+        MetricType::Counter
+    }
+}
+
+/// Turns a system counter label such as `Bytes sent` into `aeron_system_bytes_sent`. Anything
+/// after a `:`, such as the version in `Errors: version=...`, is left out of the name.
+fn system_metric_name(label: &str) -> String {
+    let label = label.split_once(':').map_or(label, |(name, _)| name);
+    let mut name = String::from("aeron_system");
+    let mut pending_separator = true;
+    for c in label.chars() {
+        if c.is_ascii_alphanumeric() {
+            if pending_separator {
+                name.push('_');
+                pending_separator = false;
+            }
+            name.push(c.to_ascii_lowercase());
+        } else {
+            pending_separator = true;
+        }
+    }
+    name
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+type Labels = Vec<(&'static str, String)>;
+
+fn metric_sample(counter: &CounterEntry) -> (String, MetricType, Labels) {
+    if counter.type_id == SYSTEM_COUNTER_TYPE_ID {
+        return (
+            system_metric_name(&counter.label),
+            system_metric_type(&counter.label),
+            Vec::new(),
+        );
+    }
+
+    let Some(name) = type_metric_name(counter.type_id) else {
+        return (
+            "aeron_counter".to_string(),
+            MetricType::Gauge,
+            vec![
+                ("id", counter.id.to_string()),
+                ("type_id", counter.type_id.to_string()),
+                ("label", counter.label.clone()),
+            ],
+        );
+    };
+
+    let labels = match counter.stream_key() {
+        Some(key) => vec![
+            ("channel", key.channel),
+            ("stream_id", key.stream_id.to_string()),
+            ("session_id", key.session_id.to_string()),
+            ("registration_id", key.registration_id.to_string()),
+        ],
+        None => counter
+            .channel()
+            .map(|channel| vec![("channel", channel), ("id", counter.id.to_string())])
+            .unwrap_or_else(|| vec![("id", counter.id.to_string())]),
+    };
+    (name.to_string(), MetricType::Gauge, labels)
+}
+
+/// Renders counters in the Prometheus text exposition format. Monotonic system counters are
+/// typed `counter`, everything else `gauge`.
+pub fn render_metrics(counters: &[CounterEntry]) -> String {
+    let mut metrics: BTreeMap<String, (MetricType, Vec<String>)> = BTreeMap::new();
+    for counter in counters {
+        let (name, metric_type, labels) = metric_sample(counter);
+        let labels: Vec<_> = labels
+            .iter()
+            .map(|(key, value)| format!("{key}=\"{}\"", escape_label_value(value)))
+            .collect();
+        let sample = if labels.is_empty() {
+            format!("{name} {}", counter.value)
+        } else {
+            format!("{name}{{{}}} {}", labels.join(","), counter.value)
+        };
+        metrics
+            .entry(name)
+            .or_insert_with(|| (metric_type, Vec::new()))
+            .1
+            .push(sample);
+    }
+
+    let mut output = String::new();
+    for (name, (metric_type, samples)) in metrics {
+        let _ = writeln!(output, "# TYPE {name} {}", metric_type.as_str());
+        for sample in samples {
+            let _ = writeln!(output, "{sample}");
+        }
+    }
+    output
+}
+
+/// Identifies the file at a path, so a CnC file recreated by a restarted driver is noticed.
+#[cfg(unix)]
+type FileIdentity = (u64, u64);
+#[cfg(not(unix))]
+type FileIdentity = std::time::SystemTime;
+
+#[cfg(unix)]
+fn file_identity(path: &str) -> Option<FileIdentity> {
+    use std::os::unix::fs::MetadataExt;
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_identity(path: &str) -> Option<FileIdentity> {
+    std::fs::metadata(path).ok()?.created().ok()
+}
+
+/// The CnC file being exported, along with what is needed to tell when it goes stale.
+struct ExportedCnc {
+    cnc: CncFile,
+    file: Option<FileIdentity>,
+    liveness_timeout: Duration,
+}
+
+impl ExportedCnc {
+    fn open(aeron_dir: &str) -> Option<Self> {
+        let cnc = CncFile::open(aeron_dir, Duration::ZERO).ok()?;
+        let liveness_timeout = cnc.constants().ok()?.client_liveness_timeout;
+        let file = file_identity(&cnc.filename());
+        Some(Self {
+            cnc,
+            file,
+            liveness_timeout,
+        })
+    }
+
+    fn is_heartbeat_stale(&self) -> bool {
+        self.cnc.driver_heartbeat_age() > self.liveness_timeout
+    }
+
+    /// `true` once the driver stopped heartbeating or the file was replaced by a new driver.
+    fn is_stale(&self) -> bool {
+        self.is_heartbeat_stale() || file_identity(&self.cnc.filename()) != self.file
+    }
+}
+
+/// Serves `/metrics` over HTTP from the counters of the driver in `aeron_dir`.
+///
+/// The CnC file is opened on the first scrape so the server can be started before the
+/// driver has created it, and re-opened whenever the driver's heartbeat goes stale or the
+/// file is replaced, so a restarted driver is picked up.
+pub struct MetricsServer {
+    running: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+// Scrapers may append query parameters, which the exporter ignores
+fn request_path(url: &str) -> &str {
+    url.split_once('?').map_or(url, |(path, _)| path)
+}
+
+impl MetricsServer {
+    pub fn start(addr: &str, aeron_dir: &str) -> common::Result<Self> {
+        let server = Server::http(addr)
+            .map_err(|error| format!("failed to bind metrics server to {addr}: {error}"))?;
+        let running = Arc::new(AtomicBool::new(true));
+        let running_clone = Arc::clone(&running);
+        let aeron_dir = aeron_dir.to_string();
+
+        let handle = std::thread::Builder::new()
+            .name("aeron-metrics".to_string())
+            .spawn(move || {
+                let mut cnc = None;
+                while running_clone.load(Ordering::Acquire) {
+                    let request = match server.recv_timeout(Duration::from_millis(100)) {
+                        Ok(Some(request)) => request,
+                        Ok(None) => continue,
+                        Err(error) => {
+                            eprintln!("metrics server failed to receive request: {error}");
+                            break;
+                        }
+                    };
+
+                    if request_path(request.url()) != "/metrics" {
+                        let _ = request
+                            .respond(Response::from_string("not found").with_status_code(404));
+                        continue;
+                    }
+
+                    if cnc.as_ref().is_none_or(ExportedCnc::is_stale) {
+                        cnc = ExportedCnc::open(&aeron_dir);
+                    }
+                    let response = match &cnc {
+                        Some(exported) if exported.is_heartbeat_stale() => {
+                            Response::from_string("driver heartbeat stale").with_status_code(503)
+                        }
+                        Some(exported) => {
+                            Response::from_string(render_metrics(&read_counters(&exported.cnc)))
+                                .with_header(
+                                    "Content-Type: text/plain; version=0.0.4"
+                                        .parse::<Header>()
+                                        .expect("valid content type header"),
+                                )
+                        }
+                        None => {
+                            Response::from_string("cnc file not available").with_status_code(503)
+                        }
+                    };
+                    let _ = request.respond(response);
+                }
+            })?;
+
+        Ok(Self {
+            running,
+            handle: Some(handle),
+        })
+    }
+}
+
+impl Drop for MetricsServer {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Release);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::counters::stream_counter_key;
+
+    #[test]
+    fn renders_system_and_stream_counters() {
+        let counters = vec![
+            CounterEntry {
+                id: 0,
+                type_id: SYSTEM_COUNTER_TYPE_ID,
+                value: 1024,
+                label: "Bytes sent".to_string(),
+                key: Vec::new(),
+            },
+            CounterEntry {
+                id: 40,
+                type_id: PUBLISHER_POSITION_TYPE_ID,
+                value: 64,
+                label: "pub-pos (sampled): 3 5 1001 aeron:ipc".to_string(),
+                key: stream_counter_key(3, 5, 1001, "aeron:ipc"),
+            },
+        ];
+
+        assert_eq!(
+            render_metrics(&counters),
+            "# TYPE aeron_publisher_position gauge\n\
+             aeron_publisher_position{channel=\"aeron:ipc\",stream_id=\"1001\",session_id=\"5\",registration_id=\"3\"} 64\n\
+             # TYPE aeron_system_bytes_sent counter\n\
+             aeron_system_bytes_sent 1024\n"
+        );
+    }
+
+    #[test]
+    fn types_system_counters_and_labels_other_counters() {
+        let counters = vec![
+            CounterEntry {
+                id: 15,
+                type_id: SYSTEM_COUNTER_TYPE_ID,
+                value: 2,
+                label: "Errors: version=1.46.5 commit=abc".to_string(),
+                key: Vec::new(),
+            },
+            CounterEntry {
+                id: 35,
+                type_id: SYSTEM_COUNTER_TYPE_ID,
+                value: 4096,
+                label: "Bytes currently mapped".to_string(),
+                key: Vec::new(),
+            },
+            CounterEntry {
+                id: 60,
+                type_id: 1001,
+                value: 7,
+                label: "custom".to_string(),
+                key: Vec::new(),
+            },
+        ];
+
+        assert_eq!(
+            render_metrics(&counters),
+            "# TYPE aeron_counter gauge\n\
+             aeron_counter{id=\"60\",type_id=\"1001\",label=\"custom\"} 7\n\
+             # TYPE aeron_system_bytes_currently_mapped gauge\n\
+             aeron_system_bytes_currently_mapped 4096\n\
+             # TYPE aeron_system_errors counter\n\
+             aeron_system_errors 2\n"
+        );
+    }
+
+    #[test]
+    fn ignores_query_string() {
+        assert_eq!(request_path("/metrics"), "/metrics");
+        assert_eq!(request_path("/metrics?name[]=aeron"), "/metrics");
+        assert_eq!(request_path("/other?metrics"), "/other");
+    }
+}
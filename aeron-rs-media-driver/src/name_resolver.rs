@@ -0,0 +1,579 @@
+use libaeron_driver_sys as aeron_driver;
+
+use std::collections::HashMap;
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use std::{mem, ptr, slice};
+
+use crate::cnc::CncFile;
+use crate::common::CallbackRegistry;
+use crate::counters::{
+    read_counters, CounterEntry, NAME_RESOLVER_CACHE_ENTRIES_TYPE_ID,
+    NAME_RESOLVER_NEIGHBORS_TYPE_ID,
+};
+use crate::interceptor::FRAME_TYPE_RES;
+use crate::sockaddr;
+
+/// Resolves the host names used in channel endpoints, in place of the driver's default
+/// DNS based resolution.
+///
+/// Resolvers run on the conductor agent thread of the driver.
+pub trait NameResolver: Send {
+    /// Resolves the host part of an endpoint. The port is kept from the endpoint.
+    ///
+    /// `uri_param_name` is the channel parameter being resolved, e.g. `endpoint`, and
+    /// `is_re_resolution` is set when the driver re-resolves a name it resolved before.
+    fn resolve(
+        &mut self,
+        name: &str,
+        uri_param_name: &str,
+        is_re_resolution: bool,
+    ) -> Option<IpAddr>;
+
+    /// Maps a name to another name before it is resolved. `None` keeps the name as is.
+    fn lookup(
+        &mut self,
+        _name: &str,
+        _uri_param_name: &str,
+        _is_re_lookup: bool,
+    ) -> Option<String> {
+        None
+    }
+
+    /// Called on every conductor duty cycle, returning the amount of work done.
+    fn do_work(&mut self, _now_ms: i64) -> i32 {
+        0
+    }
+}
+
+/// Resolves names from a fixed table, falling back to the system resolver for names not
+/// in the table.
+#[derive(Debug, Clone, Default)]
+pub struct StaticNameResolver {
+    entries: HashMap<String, IpAddr>,
+}
+
+impl StaticNameResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_entry(mut self, name: &str, address: IpAddr) -> Self {
+        self.insert(name, address);
+        self
+    }
+
+    pub fn insert(&mut self, name: &str, address: IpAddr) {
+        self.entries.insert(name.to_string(), address);
+    }
+}
+
+impl NameResolver for StaticNameResolver {
+    fn resolve(
+        &mut self,
+        name: &str,
+        _uri_param_name: &str,
+        _is_re_resolution: bool,
+    ) -> Option<IpAddr> {
+        if let Some(address) = self.entries.get(name) {
+            return Some(*address);
+        }
+        let host = name.trim_start_matches('[').trim_end_matches(']');
+        (host, 0)
+            .to_socket_addrs()
+            .ok()?
+            .next()
+            .map(|address| address.ip())
+    }
+}
+
+/// State of a driver's gossip name resolver, read from its counters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameResolverStatus {
+    /// Number of other drivers currently known to the resolver.
+    pub neighbors: i64,
+    /// Number of names in the resolver's cache.
+    pub cache_entries: i64,
+    /// Labels of the resolver counters, which include the bound address and name.
+    pub labels: Vec<String>,
+}
+
+impl NameResolverStatus {
+    /// Finds the resolver counters, returning `None` when the driver runs no name resolver.
+    pub fn from_counters(counters: &[CounterEntry]) -> Option<NameResolverStatus> {
+        let mut status = NameResolverStatus {
+            neighbors: 0,
+            cache_entries: 0,
+            labels: Vec::new(),
+        };
+        for counter in counters {
+            match counter.type_id {
+                NAME_RESOLVER_NEIGHBORS_TYPE_ID => status.neighbors += counter.value,
+                NAME_RESOLVER_CACHE_ENTRIES_TYPE_ID => status.cache_entries += counter.value,
+                _ => continue,
+            }
+            status.labels.push(counter.label.clone());
+        }
+        (!status.labels.is_empty()).then_some(status)
+    }
+
+    pub fn read(cnc: &CncFile) -> Option<NameResolverStatus> {
+        Self::from_counters(&read_counters(cnc))
+    }
+}
+
+/// A name in a driver name resolver's cache.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolverCacheEntry {
+    pub name: String,
+    /// Address of the name resolver of the driver known by `name`.
+    pub address: SocketAddr,
+    /// Time since the resolver last heard from that driver.
+    pub age: Duration,
+}
+
+// `AERON_NAME_RESOLVER_DRIVER_TIMEOUT_MS`, after which the resolver drops a silent name
+const RESOLVER_CACHE_TIMEOUT: Duration = Duration::from_secs(10);
+
+const FRAME_HEADER_LENGTH: usize = 8;
+const RES_TYPE_NAME_TO_IP4: i8 = 0x01;
+const RES_TYPE_NAME_TO_IP6: i8 = 0x02;
+const RES_SELF_FLAG: u8 = 0x80;
+
+/// The names known to a driver's gossip name resolver, enabled with
+/// `AeronContext::set_resolver_interface`.
+///
+/// The C resolver keeps its cache to itself, but sends all of it to each of its neighbors
+/// every two seconds. `AeronContext::set_resolver_cache` adds an outgoing interceptor that
+/// copies the entries of those frames here as they are sent. Entries expire when the
+/// resolver would drop them, ten seconds after it last heard from the name. Clones share
+/// the entries.
+#[derive(Debug, Clone, Default)]
+pub struct ResolverCache {
+    entries: Arc<Mutex<HashMap<String, (SocketAddr, Instant)>>>,
+}
+
+impl ResolverCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The names the resolver currently holds, sorted by name.
+    pub fn entries(&self) -> Vec<ResolverCacheEntry> {
+        self.entries_at(Instant::now())
+    }
+
+    fn entries_at(&self, now: Instant) -> Vec<ResolverCacheEntry> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, (_, last_activity)| now - *last_activity <= RESOLVER_CACHE_TIMEOUT);
+        let mut entries: Vec<_> = entries
+            .iter()
+            .map(|(name, (address, last_activity))| ResolverCacheEntry {
+                name: name.clone(),
+                address: *address,
+                age: now - *last_activity,
+            })
+            .collect();
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        entries
+    }
+
+    /// Records the entries of a resolution frame the resolver sends to its neighbors.
+    fn on_frame(&self, frame: &[u8], now: Instant) {
+        let Some(resolutions) = decode_resolutions(frame) else {
+            return;
+        };
+        let mut entries = self.entries.lock().unwrap();
+        for resolution in resolutions
+            .into_iter()
+            .filter(|resolution| !resolution.is_self)
+        {
+            let age = Duration::from_millis(resolution.age_ms.max(0) as u64);
+            let last_activity = now.checked_sub(age).unwrap_or(now);
+            entries
+                .entry(resolution.name)
+                .and_modify(|entry| {
+                    if last_activity >= entry.1 {
+                        *entry = (resolution.address, last_activity);
+                    }
+                })
+                .or_insert((resolution.address, last_activity));
+        }
+    }
+}
+
+struct Resolution {
+    name: String,
+    address: SocketAddr,
+    age_ms: i32,
+    is_self: bool,
+}
+
+/// Decodes the entries of a resolution frame, returning `None` for other frames.
+fn decode_resolutions(frame: &[u8]) -> Option<Vec<Resolution>> {
+    let frame_type = u16::from_le_bytes(frame.get(6..8)?.try_into().ok()?);
+    if u32::from(frame_type) != FRAME_TYPE_RES {
+        return None;
+    }
+
+    let mut resolutions = Vec::new();
+    let mut remaining = frame.get(FRAME_HEADER_LENGTH..)?;
+    while !remaining.is_empty() {
+        // res_type, res_flags, udp_port and age_in_ms, then the address and name length
+        let (address, name_length_offset) = match *remaining.first()? as i8 {
+            RES_TYPE_NAME_TO_IP4 => {
+                let octets: [u8; 4] = remaining.get(8..12)?.try_into().ok()?;
+                (IpAddr::V4(Ipv4Addr::from(octets)), 12)
+            }
+            RES_TYPE_NAME_TO_IP6 => {
+                let octets: [u8; 16] = remaining.get(8..24)?.try_into().ok()?;
+                (IpAddr::V6(Ipv6Addr::from(octets)), 24)
+            }
+            _ => break,
+        };
+        let port = u16::from_le_bytes(remaining.get(2..4)?.try_into().ok()?);
+        let age_ms = i32::from_le_bytes(remaining.get(4..8)?.try_into().ok()?);
+        let name_length = i16::from_le_bytes(
+            remaining
+                .get(name_length_offset..name_length_offset + 2)?
+                .try_into()
+                .ok()?,
+        );
+        // The name follows the header struct, padded to 4 bytes
+        let name_offset = (name_length_offset + 2 + 3) & !3;
+        let name_end = name_offset + name_length.max(0) as usize;
+        let name = remaining.get(name_offset..name_end)?;
+        resolutions.push(Resolution {
+            name: String::from_utf8_lossy(name).into_owned(),
+            address: SocketAddr::new(address, port),
+            age_ms,
+            is_self: remaining[1] & RES_SELF_FLAG != 0,
+        });
+
+        let entry_length = (name_end + 7) & !7;
+        remaining = remaining.get(entry_length..).unwrap_or_default();
+    }
+    Some(resolutions)
+}
+
+static CACHES: CallbackRegistry<ResolverCache> = CallbackRegistry::new();
+
+const CACHE_INTERCEPTOR_NAME: &CStr = c"rust-resolver-cache";
+
+pub(crate) fn set_cache(
+    context: *mut aeron_driver::aeron_driver_context_t,
+    cache: ResolverCache,
+) -> i32 {
+    CACHES.insert(context, Arc::new(cache));
+    unsafe {
+        // Allocated with the C allocator, the context frees its interceptor bindings
+        let bindings = libc::calloc(
+            1,
+            mem::size_of::<aeron_driver::aeron_udp_channel_interceptor_bindings_t>(),
+        ) as *mut aeron_driver::aeron_udp_channel_interceptor_bindings_t;
+        if bindings.is_null() {
+            return -1;
+        }
+        let raw = &mut *bindings;
+        raw.outgoing_init_func = Some(cache_interceptor_init);
+        raw.outgoing_send_func = Some(cache_interceptor_send);
+        raw.outgoing_close_func = Some(cache_interceptor_close);
+        raw.meta_info.name = CACHE_INTERCEPTOR_NAME.as_ptr();
+        raw.meta_info.type_ = c"interceptor".as_ptr();
+        raw.meta_info.next_interceptor_bindings =
+            aeron_driver::aeron_driver_context_get_udp_channel_outgoing_interceptors(context);
+        aeron_driver::aeron_driver_context_set_udp_channel_outgoing_interceptors(context, bindings)
+    }
+}
+
+pub(crate) fn remove_cache(context: *mut aeron_driver::aeron_driver_context_t) {
+    CACHES.remove(context);
+}
+
+unsafe extern "C" fn cache_interceptor_init(
+    interceptor_state: *mut *mut c_void,
+    context: *mut aeron_driver::aeron_driver_context_t,
+    _affinity: aeron_driver::aeron_udp_channel_transport_affinity_t,
+) -> i32 {
+    *interceptor_state = match CACHES.get(context) {
+        Some(cache) => Arc::into_raw(cache) as *mut c_void,
+        None => ptr::null_mut(),
+    };
+    0
+}
+
+unsafe extern "C" fn cache_interceptor_send(
+    interceptor_state: *mut c_void,
+    delegate: *mut aeron_driver::aeron_udp_channel_outgoing_interceptor_t,
+    transport: *mut aeron_driver::aeron_udp_channel_transport_t,
+    address: *mut aeron_driver::sockaddr_storage,
+    iov: *mut aeron_driver::iovec,
+    iov_length: usize,
+    bytes_sent: *mut i64,
+) -> i32 {
+    // The resolver sends each frame in a single iovec
+    if !interceptor_state.is_null() && iov_length == 1 {
+        let cache = &*(interceptor_state as *const ResolverCache);
+        let frame = slice::from_raw_parts((*iov).iov_base as *const u8, (*iov).iov_len);
+        cache.on_frame(frame, Instant::now());
+    }
+
+    let delegate = &*delegate;
+    match delegate.outgoing_send_func {
+        Some(send) => send(
+            delegate.interceptor_state,
+            delegate.next_interceptor,
+            transport,
+            address,
+            iov,
+            iov_length,
+            bytes_sent,
+        ),
+        None => -1,
+    }
+}
+
+unsafe extern "C" fn cache_interceptor_close(interceptor_state: *mut c_void) -> i32 {
+    if !interceptor_state.is_null() {
+        drop(Arc::from_raw(interceptor_state as *const ResolverCache));
+    }
+    0
+}
+
+/// Creates the resolver used by the driver.
+pub type NameResolverSupplier = dyn Fn() -> Box<dyn NameResolver> + Send + Sync;
+
+static SUPPLIERS: CallbackRegistry<NameResolverSupplier> = CallbackRegistry::new();
+
+const RESOLVER_NAME: &CStr = c"rust";
+
+struct ResolverState {
+    resolver: Box<dyn NameResolver>,
+    // Keeps looked up names alive for the driver, which only borrows them
+    lookups: HashMap<String, CString>,
+}
+
+pub(crate) fn set_supplier(
+    context: *mut aeron_driver::aeron_driver_context_t,
+    supplier: Arc<NameResolverSupplier>,
+) -> i32 {
+    SUPPLIERS.insert(context, supplier);
+    unsafe {
+        aeron_driver::aeron_driver_context_set_name_resolver_supplier(context, Some(supplier_fn))
+    }
+}
+
+pub(crate) fn remove_supplier(context: *mut aeron_driver::aeron_driver_context_t) {
+    SUPPLIERS.remove(context);
+}
+
+unsafe extern "C" fn supplier_fn(
+    resolver: *mut aeron_driver::aeron_name_resolver_t,
+    _args: *const c_char,
+    context: *mut aeron_driver::aeron_driver_context_t,
+) -> i32 {
+    let Some(supplier) = SUPPLIERS.get(context) else {
+        return -1;
+    };
+    let state = Box::new(ResolverState {
+        resolver: supplier(),
+        lookups: HashMap::new(),
+    });
+
+    let resolver = &mut *resolver;
+    resolver.lookup_func = Some(lookup);
+    resolver.resolve_func = Some(resolve);
+    resolver.do_work_func = Some(do_work);
+    resolver.close_func = Some(close);
+    resolver.state = Box::into_raw(state) as *mut c_void;
+    resolver.name = RESOLVER_NAME.as_ptr();
+    0
+}
+
+unsafe fn state<'a>(resolver: *mut aeron_driver::aeron_name_resolver_t) -> &'a mut ResolverState {
+    &mut *((*resolver).state as *mut ResolverState)
+}
+
+unsafe extern "C" fn resolve(
+    resolver: *mut aeron_driver::aeron_name_resolver_t,
+    name: *const c_char,
+    uri_param_name: *const c_char,
+    is_re_resolution: bool,
+    address: *mut aeron_driver::sockaddr_storage,
+) -> i32 {
+    let state = state(resolver);
+    let name = CStr::from_ptr(name).to_string_lossy();
+    let uri_param_name = CStr::from_ptr(uri_param_name).to_string_lossy();
+    match state
+        .resolver
+        .resolve(&name, &uri_param_name, is_re_resolution)
+    {
+        Some(ip) => {
+            sockaddr::write(SocketAddr::new(ip, 0), address);
+            0
+        }
+        None => -1,
+    }
+}
+
+unsafe extern "C" fn lookup(
+    resolver: *mut aeron_driver::aeron_name_resolver_t,
+    name: *const c_char,
+    uri_param_name: *const c_char,
+    is_re_lookup: bool,
+    resolved_name: *mut *const c_char,
+) -> i32 {
+    let state = state(resolver);
+    let key = CStr::from_ptr(name).to_string_lossy().into_owned();
+    let uri_param_name = CStr::from_ptr(uri_param_name).to_string_lossy();
+    match state.resolver.lookup(&key, &uri_param_name, is_re_lookup) {
+        Some(looked_up) => {
+            let Ok(looked_up) = CString::new(looked_up) else {
+                return -1;
+            };
+            *resolved_name = looked_up.as_ptr();
+            state.lookups.insert(key, looked_up);
+        }
+        None => *resolved_name = name,
+    }
+    0
+}
+
+unsafe extern "C" fn do_work(
+    resolver: *mut aeron_driver::aeron_name_resolver_t,
+    now_ms: i64,
+) -> i32 {
+    state(resolver).resolver.do_work(now_ms)
+}
+
+unsafe extern "C" fn close(resolver: *mut aeron_driver::aeron_name_resolver_t) -> i32 {
+    let state = (*resolver).state as *mut ResolverState;
+    if !state.is_null() {
+        drop(Box::from_raw(state));
+        (*resolver).state = std::ptr::null_mut();
+    }
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn static_table_then_system_resolution() {
+        let mut resolver =
+            StaticNameResolver::new().with_entry("service-a", IpAddr::V4(Ipv4Addr::LOCALHOST));
+
+        assert_eq!(
+            resolver.resolve("service-a", "endpoint", false),
+            Some(IpAddr::V4(Ipv4Addr::LOCALHOST))
+        );
+        assert_eq!(
+            resolver.resolve("127.0.0.2", "endpoint", false),
+            Some(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 2)))
+        );
+        assert_eq!(resolver.lookup("service-a", "endpoint", false), None);
+    }
+
+    fn resolution_frame(entries: &[(u8, SocketAddr, i32, &str)]) -> Vec<u8> {
+        let mut frame = vec![0u8; FRAME_HEADER_LENGTH];
+        frame[6..8].copy_from_slice(&(FRAME_TYPE_RES as u16).to_le_bytes());
+        for (flags, address, age_ms, name) in entries {
+            let start = frame.len();
+            let (res_type, octets) = match address.ip() {
+                IpAddr::V4(ip) => (RES_TYPE_NAME_TO_IP4, ip.octets().to_vec()),
+                IpAddr::V6(ip) => (RES_TYPE_NAME_TO_IP6, ip.octets().to_vec()),
+            };
+            frame.push(res_type as u8);
+            frame.push(*flags);
+            frame.extend_from_slice(&address.port().to_le_bytes());
+            frame.extend_from_slice(&age_ms.to_le_bytes());
+            frame.extend_from_slice(&octets);
+            frame.extend_from_slice(&(name.len() as i16).to_le_bytes());
+            frame.resize(start + ((frame.len() - start + 3) & !3), 0);
+            frame.extend_from_slice(name.as_bytes());
+            frame.resize(start + ((frame.len() - start + 7) & !7), 0);
+        }
+        let frame_length = frame.len() as i32;
+        frame[0..4].copy_from_slice(&frame_length.to_le_bytes());
+        frame
+    }
+
+    #[test]
+    fn resolver_cache_from_sent_resolutions() {
+        let driver_b: SocketAddr = "10.0.0.2:8050".parse().unwrap();
+        let driver_c: SocketAddr = "[fd00::3]:8050".parse().unwrap();
+        let own: SocketAddr = "10.0.0.1:8050".parse().unwrap();
+        let cache = ResolverCache::new();
+        let now = Instant::now();
+
+        cache.on_frame(
+            &resolution_frame(&[
+                (0, driver_b, 1000, "driver-b"),
+                (0, driver_c, 9000, "driver-c"),
+                (RES_SELF_FLAG, own, 0, "driver-a"),
+            ]),
+            now,
+        );
+        // Not a resolution frame
+        cache.on_frame(&[0u8; 32], now);
+
+        assert_eq!(
+            cache.entries_at(now),
+            vec![
+                ResolverCacheEntry {
+                    name: "driver-b".to_string(),
+                    address: driver_b,
+                    age: Duration::from_secs(1),
+                },
+                ResolverCacheEntry {
+                    name: "driver-c".to_string(),
+                    address: driver_c,
+                    age: Duration::from_secs(9),
+                },
+            ]
+        );
+
+        let later = now + Duration::from_secs(2);
+        let names: Vec<_> = cache
+            .entries_at(later)
+            .into_iter()
+            .map(|entry| entry.name)
+            .collect();
+        assert_eq!(names, vec!["driver-b".to_string()]);
+    }
+
+    #[test]
+    fn resolver_status_from_counters() {
+        let counter = |type_id, value, label: &str| CounterEntry {
+            id: type_id,
+            type_id,
+            value,
+            label: label.to_string(),
+            key: Vec::new(),
+        };
+        assert_eq!(
+            NameResolverStatus::from_counters(&[counter(0, 5, "Bytes sent")]),
+            None
+        );
+
+        let counters = [
+            counter(
+                NAME_RESOLVER_NEIGHBORS_TYPE_ID,
+                2,
+                "Resolver neighbors: bound 0.0.0.0:8050",
+            ),
+            counter(
+                NAME_RESOLVER_CACHE_ENTRIES_TYPE_ID,
+                3,
+                "Resolver cache entries: name driver-a",
+            ),
+        ];
+        let status = NameResolverStatus::from_counters(&counters).unwrap();
+        assert_eq!(status.neighbors, 2);
+        assert_eq!(status.cache_entries, 3);
+        assert_eq!(status.labels.len(), 2);
+    }
+}
@@ -0,0 +1,152 @@
+use std::time::{Duration, Instant};
+
+/// Counts messages, bytes and back pressure and prints the rates once per interval.
+///
+/// Callers drive it from their duty loop with `maybe_report`, so no extra thread is needed.
+pub struct RateReporter {
+    interval: Duration,
+    last_report: Instant,
+    messages: u64,
+    bytes: u64,
+    back_pressure: u64,
+    total_messages: u64,
+    total_bytes: u64,
+}
+
+impl RateReporter {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last_report: Instant::now(),
+            messages: 0,
+            bytes: 0,
+            back_pressure: 0,
+            total_messages: 0,
+            total_bytes: 0,
+        }
+    }
+
+    pub fn on_message(&mut self, length: usize) {
+        self.messages += 1;
+        self.bytes += length as u64;
+    }
+
+    pub fn on_back_pressure(&mut self) {
+        self.back_pressure += 1;
+    }
+
+    pub fn total_messages(&self) -> u64 {
+        self.total_messages + self.messages
+    }
+
+    /// Prints and resets the rates if the interval has elapsed since the last report.
+    pub fn maybe_report(&mut self) {
+        let elapsed = self.last_report.elapsed();
+        if elapsed >= self.interval {
+            self.take_rates(elapsed).print();
+        }
+    }
+
+    /// Prints the rates of the partial interval since the last report, if anything happened
+    /// in it. Call once the loop driving `maybe_report` has stopped.
+    pub fn finish(&mut self) {
+        if self.messages > 0 || self.back_pressure > 0 {
+            let elapsed = self.last_report.elapsed();
+            self.take_rates(elapsed).print();
+        }
+    }
+
+    /// Computes the rates over `elapsed`, folds the interval into the totals and resets it.
+    fn take_rates(&mut self, elapsed: Duration) -> Rates {
+        self.total_messages += self.messages;
+        self.total_bytes += self.bytes;
+
+        // An interval cut short on shutdown can be arbitrarily small, but never zero
+        let seconds = elapsed.as_secs_f64().max(f64::MIN_POSITIVE);
+        let rates = Rates {
+            messages_per_sec: self.messages as f64 / seconds,
+            bytes_per_sec: self.bytes as f64 / seconds,
+            back_pressure: self.back_pressure,
+            total_messages: self.total_messages,
+            total_bytes: self.total_bytes,
+        };
+
+        self.messages = 0;
+        self.bytes = 0;
+        self.back_pressure = 0;
+        self.last_report = Instant::now();
+        rates
+    }
+}
+
+/// The rates of one reporting interval and the totals up to its end.
+#[derive(Debug, Clone, PartialEq)]
+struct Rates {
+    messages_per_sec: f64,
+    bytes_per_sec: f64,
+    back_pressure: u64,
+    total_messages: u64,
+    total_bytes: u64,
+}
+
+impl Rates {
+    fn print(&self) {
+        println!(
+            "{:.0} msgs/sec, {:.0} bytes/sec, {} back pressured, totals {} messages {} MB",
+            self.messages_per_sec,
+            self.bytes_per_sec,
+            self.back_pressure,
+            self.total_messages,
+            self.total_bytes / (1024 * 1024)
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rates_over_interval() {
+        let mut reporter = RateReporter::new(Duration::from_secs(1));
+        for _ in 0..3 {
+            reporter.on_message(100);
+        }
+        reporter.on_back_pressure();
+
+        assert_eq!(
+            reporter.take_rates(Duration::from_millis(500)),
+            Rates {
+                messages_per_sec: 6.0,
+                bytes_per_sec: 600.0,
+                back_pressure: 1,
+                total_messages: 3,
+                total_bytes: 300,
+            }
+        );
+    }
+
+    #[test]
+    fn totals_accumulate_across_intervals() {
+        let mut reporter = RateReporter::new(Duration::from_secs(1));
+        reporter.on_message(1024);
+        reporter.take_rates(Duration::from_secs(1));
+        reporter.on_message(2048);
+        reporter.on_message(2048);
+
+        let rates = reporter.take_rates(Duration::from_secs(2));
+        assert_eq!(rates.messages_per_sec, 1.0);
+        assert_eq!(rates.bytes_per_sec, 2048.0);
+        assert_eq!(rates.back_pressure, 0);
+        assert_eq!((rates.total_messages, rates.total_bytes), (3, 5120));
+        assert_eq!(reporter.total_messages(), 3);
+    }
+
+    #[test]
+    fn zero_length_interval_does_not_divide_by_zero() {
+        let mut reporter = RateReporter::new(Duration::from_secs(1));
+        let rates = reporter.take_rates(Duration::ZERO);
+        assert_eq!(rates.messages_per_sec, 0.0);
+        assert_eq!(rates.bytes_per_sec, 0.0);
+    }
+}
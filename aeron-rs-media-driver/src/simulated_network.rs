@@ -0,0 +1,319 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+
+use crate::transport::{TransportAddresses, UdpChannelTransport, UdpChannelTransportBindings};
+
+const FIRST_EPHEMERAL_PORT: u16 = 32768;
+
+/// An in-process network connecting drivers in the same process, for deterministic
+/// multi-driver tests without real sockets.
+///
+/// Each driver is given the bindings of one simulated host address. Tests can partition
+/// hosts from each other and reorder datagrams with a seeded random number generator.
+/// Only unicast channels are supported.
+#[derive(Clone, Default)]
+pub struct SimulatedNetwork {
+    state: Arc<Mutex<NetworkState>>,
+}
+
+#[derive(Default)]
+struct NetworkState {
+    endpoints: HashMap<SocketAddr, VecDeque<(Vec<u8>, SocketAddr)>>,
+    partitions: HashSet<(IpAddr, IpAddr)>,
+    reordering: Option<Reordering>,
+    next_port: u16,
+    dropped: u64,
+}
+
+struct Reordering {
+    probability: f64,
+    random: u64,
+}
+
+impl Reordering {
+    // xorshift64*, good enough to pick which datagrams to reorder
+    fn next_f64(&mut self) -> f64 {
+        self.random ^= self.random >> 12;
+        self.random ^= self.random << 25;
+        self.random ^= self.random >> 27;
+        let value = self.random.wrapping_mul(0x2545_F491_4F6C_DD1D);
+        (value >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+fn host_pair(a: IpAddr, b: IpAddr) -> (IpAddr, IpAddr) {
+    if a <= b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+impl NetworkState {
+    fn bind(&mut self, address: SocketAddr) -> io::Result<SocketAddr> {
+        let mut address = address;
+        if address.port() == 0 {
+            if self.next_port < FIRST_EPHEMERAL_PORT {
+                self.next_port = FIRST_EPHEMERAL_PORT;
+            }
+            while self
+                .endpoints
+                .contains_key(&SocketAddr::new(address.ip(), self.next_port))
+            {
+                self.next_port = self
+                    .next_port
+                    .checked_add(1)
+                    .unwrap_or(FIRST_EPHEMERAL_PORT);
+            }
+            address.set_port(self.next_port);
+            self.next_port = self
+                .next_port
+                .checked_add(1)
+                .unwrap_or(FIRST_EPHEMERAL_PORT);
+        }
+        if self.endpoints.contains_key(&address) {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                format!("{address} is already bound"),
+            ));
+        }
+        self.endpoints.insert(address, VecDeque::new());
+        Ok(address)
+    }
+
+    fn deliver(&mut self, source: SocketAddr, destination: SocketAddr, datagram: &[u8]) {
+        if self
+            .partitions
+            .contains(&host_pair(source.ip(), destination.ip()))
+        {
+            self.dropped += 1;
+            return;
+        }
+        let Some(queue) = self.endpoints.get_mut(&destination) else {
+            self.dropped += 1;
+            return;
+        };
+
+        queue.push_back((datagram.to_vec(), source));
+        if let Some(reordering) = &mut self.reordering {
+            if queue.len() > 1 && reordering.next_f64() < reordering.probability {
+                let last = queue.len() - 1;
+                queue.swap(last - 1, last);
+            }
+        }
+    }
+}
+
+impl SimulatedNetwork {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Transport bindings for a driver running on the simulated host `host`. Wildcard
+    /// binds are bound to `host`.
+    pub fn bindings(&self, host: IpAddr) -> SimulatedBindings {
+        SimulatedBindings {
+            network: self.clone(),
+            host,
+        }
+    }
+
+    /// Drops every datagram between hosts `a` and `b` until they are healed.
+    pub fn partition(&self, a: IpAddr, b: IpAddr) {
+        self.state
+            .lock()
+            .unwrap()
+            .partitions
+            .insert(host_pair(a, b));
+    }
+
+    pub fn heal(&self, a: IpAddr, b: IpAddr) {
+        self.state
+            .lock()
+            .unwrap()
+            .partitions
+            .remove(&host_pair(a, b));
+    }
+
+    pub fn heal_all(&self) {
+        self.state.lock().unwrap().partitions.clear();
+    }
+
+    /// Swaps each datagram with the one queued before it with `probability`, using a
+    /// generator seeded with `seed`.
+    pub fn set_reordering(&self, probability: f64, seed: u64) {
+        self.state.lock().unwrap().reordering = Some(Reordering {
+            probability,
+            // xorshift must not start from zero
+            random: seed | 1,
+        });
+    }
+
+    pub fn clear_reordering(&self) {
+        self.state.lock().unwrap().reordering = None;
+    }
+
+    /// Number of datagrams dropped by partitions or sent to unbound addresses.
+    pub fn dropped_datagrams(&self) -> u64 {
+        self.state.lock().unwrap().dropped
+    }
+}
+
+/// The transport bindings of one host on a `SimulatedNetwork`.
+#[derive(Clone)]
+pub struct SimulatedBindings {
+    network: SimulatedNetwork,
+    host: IpAddr,
+}
+
+impl UdpChannelTransportBindings for SimulatedBindings {
+    fn init(&self, addresses: &TransportAddresses) -> io::Result<Box<dyn UdpChannelTransport>> {
+        let is_multicast = |address: &Option<SocketAddr>| {
+            address.is_some_and(|address| address.ip().is_multicast())
+        };
+        if is_multicast(&addresses.bind) || is_multicast(&addresses.connect) {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "multicast is not supported by the simulated network",
+            ));
+        }
+
+        let mut bind = addresses
+            .bind
+            .unwrap_or_else(|| SocketAddr::new(self.host, 0));
+        if bind.ip().is_unspecified() {
+            bind.set_ip(self.host);
+        }
+        let local = self.network.state.lock().unwrap().bind(bind)?;
+        Ok(Box::new(SimulatedTransport {
+            network: self.network.clone(),
+            local,
+        }))
+    }
+}
+
+struct SimulatedTransport {
+    network: SimulatedNetwork,
+    local: SocketAddr,
+}
+
+impl UdpChannelTransport for SimulatedTransport {
+    fn sendmmsg(&mut self, destination: SocketAddr, datagrams: &[&[u8]]) -> io::Result<usize> {
+        let mut state = self.network.state.lock().unwrap();
+        for datagram in datagrams {
+            state.deliver(self.local, destination, datagram);
+        }
+        Ok(datagrams.len())
+    }
+
+    fn recvmmsg(
+        &mut self,
+        limit: usize,
+        on_datagram: &mut dyn FnMut(&[u8], SocketAddr),
+    ) -> io::Result<usize> {
+        let mut state = self.network.state.lock().unwrap();
+        let Some(queue) = state.endpoints.get_mut(&self.local) else {
+            return Ok(0);
+        };
+        let mut received = 0;
+        while received < limit {
+            let Some((datagram, source)) = queue.pop_front() else {
+                break;
+            };
+            on_datagram(&datagram, source);
+            received += 1;
+        }
+        Ok(received)
+    }
+
+    fn local_addr(&self) -> Option<SocketAddr> {
+        Some(self.local)
+    }
+}
+
+impl Drop for SimulatedTransport {
+    fn drop(&mut self) {
+        self.network
+            .state
+            .lock()
+            .unwrap()
+            .endpoints
+            .remove(&self.local);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    fn host(last: u8) -> IpAddr {
+        IpAddr::V4(Ipv4Addr::new(10, 0, 0, last))
+    }
+
+    fn open(
+        bindings: &SimulatedBindings,
+        bind: Option<SocketAddr>,
+    ) -> Box<dyn UdpChannelTransport> {
+        bindings
+            .init(&TransportAddresses {
+                bind,
+                connect: None,
+                multicast_interface: None,
+            })
+            .unwrap()
+    }
+
+    fn receive_all(transport: &mut Box<dyn UdpChannelTransport>) -> Vec<Vec<u8>> {
+        let mut received = Vec::new();
+        transport
+            .recvmmsg(usize::MAX, &mut |datagram, _| {
+                received.push(datagram.to_vec())
+            })
+            .unwrap();
+        received
+    }
+
+    #[test]
+    fn partition_drops_and_heal_restores() {
+        let network = SimulatedNetwork::new();
+        let receiver_address = SocketAddr::new(host(2), 40123);
+        let mut sender = open(&network.bindings(host(1)), None);
+        let mut receiver = open(&network.bindings(host(2)), Some(receiver_address));
+        assert_eq!(sender.local_addr().unwrap().ip(), host(1));
+
+        sender.sendmmsg(receiver_address, &[b"a"]).unwrap();
+        network.partition(host(2), host(1));
+        sender.sendmmsg(receiver_address, &[b"b"]).unwrap();
+        network.heal(host(1), host(2));
+        sender.sendmmsg(receiver_address, &[b"c"]).unwrap();
+
+        assert_eq!(
+            receive_all(&mut receiver),
+            vec![b"a".to_vec(), b"c".to_vec()]
+        );
+        assert_eq!(network.dropped_datagrams(), 1);
+    }
+
+    #[test]
+    fn reordering_is_seeded() {
+        let received_with_seed = |seed| {
+            let network = SimulatedNetwork::new();
+            network.set_reordering(0.5, seed);
+            let receiver_address = SocketAddr::new(host(2), 40123);
+            let mut sender = open(&network.bindings(host(1)), None);
+            let mut receiver = open(&network.bindings(host(2)), Some(receiver_address));
+            for i in 0..32u8 {
+                sender.sendmmsg(receiver_address, &[&[i]]).unwrap();
+            }
+            receive_all(&mut receiver)
+        };
+
+        let received = received_with_seed(7);
+        assert_eq!(received, received_with_seed(7));
+        assert_eq!(received.len(), 32);
+        assert_ne!(received, (0..32u8).map(|i| vec![i]).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,63 @@
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+
+/// Reads an IPv4 or IPv6 address from a `sockaddr_storage` filled in by the driver.
+///
+/// # Safety
+///
+/// `addr` must be null or point to a valid `sockaddr_storage`.
+pub(crate) unsafe fn from_raw<S>(addr: *const S) -> Option<SocketAddr> {
+    let storage = (addr as *const libc::sockaddr_storage).as_ref()?;
+    match storage.ss_family as libc::c_int {
+        libc::AF_INET => {
+            let addr = &*(storage as *const _ as *const libc::sockaddr_in);
+            Some(SocketAddr::V4(SocketAddrV4::new(
+                Ipv4Addr::from(u32::from_be(addr.sin_addr.s_addr)),
+                u16::from_be(addr.sin_port),
+            )))
+        }
+        libc::AF_INET6 => {
+            let addr = &*(storage as *const _ as *const libc::sockaddr_in6);
+            Some(SocketAddr::V6(SocketAddrV6::new(
+                Ipv6Addr::from(addr.sin6_addr.s6_addr),
+                u16::from_be(addr.sin6_port),
+                u32::from_be(addr.sin6_flowinfo),
+                addr.sin6_scope_id,
+            )))
+        }
+        _ => None,
+    }
+}
+
+/// Writes `address` into a `sockaddr_storage` for the driver.
+///
+/// # Safety
+///
+/// `storage` must point to a writable `sockaddr_storage`.
+pub(crate) unsafe fn write<S>(address: SocketAddr, storage: *mut S) {
+    let storage = storage as *mut libc::sockaddr_storage;
+    std::ptr::write_bytes(storage, 0, 1);
+    match address {
+        SocketAddr::V4(address) => {
+            let raw = &mut *(storage as *mut libc::sockaddr_in);
+            raw.sin_family = libc::AF_INET as libc::sa_family_t;
+            raw.sin_port = address.port().to_be();
+            raw.sin_addr.s_addr = u32::from(*address.ip()).to_be();
+        }
+        SocketAddr::V6(address) => {
+            let raw = &mut *(storage as *mut libc::sockaddr_in6);
+            raw.sin6_family = libc::AF_INET6 as libc::sa_family_t;
+            raw.sin6_port = address.port().to_be();
+            raw.sin6_flowinfo = address.flowinfo().to_be();
+            raw.sin6_addr.s6_addr = address.ip().octets();
+            raw.sin6_scope_id = address.scope_id();
+        }
+    }
+}
+
+/// The length of the `sockaddr` variant `write` fills in for `address`.
+pub(crate) fn raw_len(address: &SocketAddr) -> usize {
+    match address {
+        SocketAddr::V4(_) => std::mem::size_of::<libc::sockaddr_in>(),
+        SocketAddr::V6(_) => std::mem::size_of::<libc::sockaddr_in6>(),
+    }
+}
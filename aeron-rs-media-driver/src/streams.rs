@@ -0,0 +1,143 @@
+use std::collections::BTreeMap;
+
+use crate::counters::{
+    CounterEntry, PUBLISHER_LIMIT_TYPE_ID, PUBLISHER_POSITION_TYPE_ID, RECEIVER_HWM_TYPE_ID,
+    RECEIVER_POSITION_TYPE_ID, SENDER_BPE_TYPE_ID, SENDER_LIMIT_TYPE_ID, SENDER_POSITION_TYPE_ID,
+    SUBSCRIBER_POSITION_TYPE_ID,
+};
+
+/// One position counter of a stream, named by the prefix of its label, e.g. `snd-pos`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamPosition {
+    pub name: String,
+    pub type_id: i32,
+    pub registration_id: i64,
+    pub value: i64,
+}
+
+/// Every position counter of one `(channel, stream id, session id)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamPositions {
+    pub channel: String,
+    pub stream_id: i32,
+    pub session_id: i32,
+    pub positions: Vec<StreamPosition>,
+}
+
+/// Where a counter sits on the path from publisher to subscriber, used to order positions.
+fn pipeline_rank(type_id: i32) -> usize {
+    [
+        PUBLISHER_POSITION_TYPE_ID,
+        PUBLISHER_LIMIT_TYPE_ID,
+        SENDER_POSITION_TYPE_ID,
+        SENDER_LIMIT_TYPE_ID,
+        SENDER_BPE_TYPE_ID,
+        RECEIVER_HWM_TYPE_ID,
+        RECEIVER_POSITION_TYPE_ID,
+        SUBSCRIBER_POSITION_TYPE_ID,
+    ]
+    .iter()
+    .position(|&ranked| ranked == type_id)
+    .unwrap_or(usize::MAX)
+}
+
+/// The counter name is the label up to the first `:`, e.g. `pub-pos (sampled)`.
+fn position_name(label: &str) -> String {
+    label
+        .split_once(':')
+        .map_or(label, |(name, _)| name)
+        .trim()
+        .to_string()
+}
+
+/// Groups stream counters by channel, stream id and session id, ordering each stream's
+/// positions from publisher to subscriber.
+pub fn group_by_stream(counters: &[CounterEntry]) -> Vec<StreamPositions> {
+    let mut streams: BTreeMap<(String, i32, i32), Vec<StreamPosition>> = BTreeMap::new();
+
+    for counter in counters {
+        let Some(key) = counter.stream_key() else {
+            continue;
+        };
+        streams
+            .entry((key.channel, key.stream_id, key.session_id))
+            .or_default()
+            .push(StreamPosition {
+                name: position_name(&counter.label),
+                type_id: counter.type_id,
+                registration_id: key.registration_id,
+                value: counter.value,
+            });
+    }
+
+    streams
+        .into_iter()
+        .map(|((channel, stream_id, session_id), mut positions)| {
+            positions.sort_by_key(|position| {
+                (pipeline_rank(position.type_id), position.registration_id)
+            });
+            StreamPositions {
+                channel,
+                stream_id,
+                session_id,
+                positions,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::counters::stream_counter_key;
+
+    fn counter(type_id: i32, label: &str, stream_id: i32, value: i64) -> CounterEntry {
+        CounterEntry {
+            id: 0,
+            type_id,
+            value,
+            label: label.to_string(),
+            key: stream_counter_key(1, 9, stream_id, "aeron:ipc"),
+        }
+    }
+
+    #[test]
+    fn groups_and_orders_positions() {
+        let counters = vec![
+            counter(
+                SUBSCRIBER_POSITION_TYPE_ID,
+                "sub-pos: 1 9 10 aeron:ipc @0",
+                10,
+                64,
+            ),
+            counter(
+                PUBLISHER_LIMIT_TYPE_ID,
+                "pub-lmt: 1 9 10 aeron:ipc",
+                10,
+                1024,
+            ),
+            counter(
+                PUBLISHER_POSITION_TYPE_ID,
+                "pub-pos (sampled): 1 9 10 aeron:ipc",
+                10,
+                128,
+            ),
+            counter(
+                PUBLISHER_POSITION_TYPE_ID,
+                "pub-pos (sampled): 1 9 11 aeron:ipc",
+                11,
+                0,
+            ),
+        ];
+
+        let streams = group_by_stream(&counters);
+        assert_eq!(streams.len(), 2);
+        assert_eq!(streams[0].stream_id, 10);
+        let names: Vec<_> = streams[0]
+            .positions
+            .iter()
+            .map(|position| position.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["pub-pos (sampled)", "pub-lmt", "sub-pos"]);
+    }
+}
@@ -0,0 +1,136 @@
+use libaeron_driver_sys as aeron_driver;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::client::AeronClientContext;
+use crate::cnc::{check_driver, CncFile};
+use crate::common;
+use crate::media_driver::{AeronContext, AeronDriver};
+
+/// How long `launch` waits for the driver's CnC file and first heartbeat.
+const READY_TIMEOUT: Duration = Duration::from_secs(10);
+
+static NEXT_DRIVER_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// A media driver for tests, running in its own throwaway aeron dir.
+///
+/// The driver uses the shared threading mode, small term buffers and short timeouts so
+/// tests start quickly and notice lost clients and images fast. The driver is closed and
+/// its directory deleted when this is dropped.
+pub struct TestMediaDriver {
+    // Declared before `context` so the driver is closed before its context.
+    driver: AeronDriver,
+    context: AeronContext,
+    dir: String,
+}
+
+impl TestMediaDriver {
+    /// Launches a driver with the default test configuration.
+    pub fn launch() -> common::Result<Self> {
+        Self::launch_with(|_| Ok(()))
+    }
+
+    /// Launches a driver, letting `configure` adjust the test configuration before start.
+    pub fn launch_with(
+        configure: impl FnOnce(&AeronContext) -> common::Result<()>,
+    ) -> common::Result<Self> {
+        let context = AeronContext::new()?;
+        context.set_dir(&unique_dir())?;
+        context.set_dir_delete_on_start(true)?;
+        context.set_dir_delete_on_shutdown(true)?;
+        context.set_threading_mode(
+            aeron_driver::aeron_threading_mode_enum::AERON_THREADING_MODE_SHARED,
+        )?;
+        context.set_term_buffer_sparse_file(true)?;
+        context.set_term_buffer_length(64 * 1024)?;
+        context.set_ipc_term_buffer_length(64 * 1024)?;
+        context.set_client_liveness_timeout_ns(Duration::from_secs(5).as_nanos() as u64)?;
+        context.set_image_liveness_timeout_ns(Duration::from_secs(2).as_nanos() as u64)?;
+        context.set_publication_linger_timeout_ns(Duration::from_millis(100).as_nanos() as u64)?;
+        context.set_timer_interval_ns(Duration::from_millis(100).as_nanos() as u64)?;
+        configure(&context)?;
+        // `configure` may have moved the driver to another dir
+        let dir = context.get_dir();
+
+        let driver = AeronDriver::new(&context)?;
+        driver.start()?;
+
+        let test_driver = Self {
+            driver,
+            context,
+            dir,
+        };
+        test_driver.await_ready()?;
+        Ok(test_driver)
+    }
+
+    fn await_ready(&self) -> common::Result<()> {
+        let deadline = Instant::now() + READY_TIMEOUT;
+        CncFile::open(&self.dir, READY_TIMEOUT)?;
+        loop {
+            let status = check_driver(&self.dir, READY_TIMEOUT);
+            if status.is_active() {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                return Err(format!("test driver in {} not ready: {status}", self.dir).into());
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    pub fn dir(&self) -> &str {
+        &self.dir
+    }
+
+    pub fn context(&self) -> &AeronContext {
+        &self.context
+    }
+
+    pub fn driver(&self) -> &AeronDriver {
+        &self.driver
+    }
+
+    /// A client context pointed at this driver's aeron dir.
+    pub fn client_context(&self) -> common::Result<AeronClientContext> {
+        let context = AeronClientContext::new()?;
+        context.set_dir(&self.dir)?;
+        Ok(context)
+    }
+}
+
+fn unique_dir() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    std::env::temp_dir()
+        .join(format!(
+            "aeron-test-{}-{}-{}",
+            std::process::id(),
+            NEXT_DRIVER_ID.fetch_add(1, Ordering::Relaxed),
+            nanos
+        ))
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn launches_and_cleans_up() {
+        let driver = TestMediaDriver::launch().unwrap();
+        let dir = driver.dir().to_string();
+        assert!(Path::new(&dir).join("cnc.dat").exists());
+
+        let other = TestMediaDriver::launch().unwrap();
+        assert_ne!(other.dir(), dir);
+
+        drop(driver);
+        assert!(!Path::new(&dir).exists());
+    }
+}
@@ -0,0 +1,513 @@
+use libaeron_driver_sys as aeron_driver;
+
+use std::ffi::c_char;
+use std::io;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket};
+use std::os::fd::AsRawFd;
+use std::ptr;
+use std::slice;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use crate::common::CallbackRegistry;
+use crate::sockaddr;
+
+/// Addresses the driver opens a UDP channel transport with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransportAddresses {
+    pub bind: Option<SocketAddr>,
+    pub connect: Option<SocketAddr>,
+    pub multicast_interface: Option<SocketAddr>,
+}
+
+/// One UDP socket of the driver, replaced by a Rust implementation.
+///
+/// Transports are called from the driver agent that owns the channel endpoint.
+pub trait UdpChannelTransport: Send {
+    /// Sends each datagram to `destination`, returning how many were sent.
+    fn sendmmsg(&mut self, destination: SocketAddr, datagrams: &[&[u8]]) -> io::Result<usize>;
+
+    /// Receives up to `limit` datagrams without blocking, passing each to `on_datagram`
+    /// with its source address. Returns how many were received.
+    ///
+    /// Datagrams longer than the driver's receive buffer, which holds one MTU, are dropped
+    /// rather than truncated and counted by `oversized_datagrams`.
+    fn recvmmsg(
+        &mut self,
+        limit: usize,
+        on_datagram: &mut dyn FnMut(&[u8], SocketAddr),
+    ) -> io::Result<usize>;
+
+    /// The address the transport is bound to, shown in the driver's socket counters.
+    fn local_addr(&self) -> Option<SocketAddr>;
+
+    /// Receive buffer length reported to the driver when it sizes receiver windows.
+    fn so_rcvbuf(&self) -> usize {
+        128 * 1024
+    }
+
+    fn close(&mut self) {}
+}
+
+/// Creates the UDP channel transports of a driver. The driver's pollers receive from each
+/// transport in turn through `UdpChannelTransport::recvmmsg`.
+pub trait UdpChannelTransportBindings: Send + Sync {
+    fn init(&self, addresses: &TransportAddresses) -> io::Result<Box<dyn UdpChannelTransport>>;
+}
+
+/// Largest payload of a UDP datagram.
+const MAX_UDP_PAYLOAD_LENGTH: usize = 65507;
+
+/// Bindings backed by non-blocking `std::net::UdpSocket`s, so Rust transports such as
+/// `interceptor::ImpairedBindings` can run over real UDP. Only unicast channels are
+/// supported.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UdpSocketBindings;
+
+impl UdpChannelTransportBindings for UdpSocketBindings {
+    fn init(&self, addresses: &TransportAddresses) -> io::Result<Box<dyn UdpChannelTransport>> {
+        let is_multicast = |address: Option<SocketAddr>| {
+            address.is_some_and(|address| address.ip().is_multicast())
+        };
+        if is_multicast(addresses.bind) || is_multicast(addresses.connect) {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "udp socket bindings only support unicast channels",
+            ));
+        }
+
+        let bind = match (addresses.bind, addresses.connect) {
+            (Some(bind), _) => bind,
+            (None, Some(connect)) if connect.is_ipv6() => (Ipv6Addr::UNSPECIFIED, 0).into(),
+            (None, _) => (Ipv4Addr::UNSPECIFIED, 0).into(),
+        };
+        let socket = UdpSocket::bind(bind)?;
+        socket.set_nonblocking(true)?;
+        Ok(Box::new(UdpSocketTransport {
+            socket,
+            buffer: vec![0; MAX_UDP_PAYLOAD_LENGTH],
+        }))
+    }
+}
+
+struct UdpSocketTransport {
+    socket: UdpSocket,
+    buffer: Vec<u8>,
+}
+
+impl UdpChannelTransport for UdpSocketTransport {
+    fn sendmmsg(&mut self, destination: SocketAddr, datagrams: &[&[u8]]) -> io::Result<usize> {
+        let mut sent = 0;
+        for datagram in datagrams {
+            match self.socket.send_to(datagram, destination) {
+                Ok(_) => sent += 1,
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => break,
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(sent)
+    }
+
+    fn recvmmsg(
+        &mut self,
+        limit: usize,
+        on_datagram: &mut dyn FnMut(&[u8], SocketAddr),
+    ) -> io::Result<usize> {
+        let mut received = 0;
+        while received < limit {
+            match self.socket.recv_from(&mut self.buffer) {
+                Ok((length, source)) => {
+                    on_datagram(&self.buffer[..length], source);
+                    received += 1;
+                }
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => break,
+                // An ICMP error for an earlier send, as UDP does not know its peers
+                Err(error) if error.kind() == io::ErrorKind::ConnectionRefused => continue,
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(received)
+    }
+
+    fn local_addr(&self) -> Option<SocketAddr> {
+        self.socket.local_addr().ok()
+    }
+
+    fn so_rcvbuf(&self) -> usize {
+        let mut so_rcvbuf: libc::c_int = 0;
+        let mut length = std::mem::size_of::<libc::c_int>() as libc::socklen_t;
+        let result = unsafe {
+            libc::getsockopt(
+                self.socket.as_raw_fd(),
+                libc::SOL_SOCKET,
+                libc::SO_RCVBUF,
+                &mut so_rcvbuf as *mut libc::c_int as *mut libc::c_void,
+                &mut length,
+            )
+        };
+        if result < 0 {
+            return 128 * 1024;
+        }
+        so_rcvbuf as usize
+    }
+}
+
+static OVERSIZED_DATAGRAMS: AtomicU64 = AtomicU64::new(0);
+
+static BINDINGS: CallbackRegistry<dyn UdpChannelTransportBindings> = CallbackRegistry::new();
+
+/// Number of received datagrams dropped in this process because they did not fit the
+/// driver's receive buffer.
+pub fn oversized_datagrams() -> u64 {
+    OVERSIZED_DATAGRAMS.load(Ordering::Relaxed)
+}
+
+// Held in the `bindings_clientd` of the C transport. Locked as the conductor reads the
+// bound address while the owning agent sends and receives.
+struct TransportState {
+    transport: Box<dyn UdpChannelTransport>,
+    // Destination of sends that do not carry one, updated when the driver reconnects
+    connect: Option<SocketAddr>,
+}
+
+unsafe fn transport_state<'a>(
+    transport: *mut aeron_driver::aeron_udp_channel_transport_t,
+) -> Option<&'a Mutex<TransportState>> {
+    ((*transport).bindings_clientd as *const Mutex<TransportState>).as_ref()
+}
+
+// Held in the `bindings_clientd` of the C poller, which only its agent uses
+type PollerTransports = Vec<*mut aeron_driver::aeron_udp_channel_transport_t>;
+
+unsafe fn poller_transports<'a>(
+    poller: *mut aeron_driver::aeron_udp_transport_poller_t,
+) -> Option<&'a mut PollerTransports> {
+    ((*poller).bindings_clientd as *mut PollerTransports).as_mut()
+}
+
+struct RawBindings(aeron_driver::aeron_udp_channel_transport_bindings_t);
+
+// The bindings only hold function pointers, which the driver reads from any agent
+unsafe impl Send for RawBindings {}
+unsafe impl Sync for RawBindings {}
+
+fn raw_bindings() -> &'static RawBindings {
+    static RAW_BINDINGS: OnceLock<RawBindings> = OnceLock::new();
+    RAW_BINDINGS.get_or_init(|| {
+        let mut raw: aeron_driver::aeron_udp_channel_transport_bindings_t =
+            unsafe { std::mem::zeroed() };
+        raw.init_func = Some(init);
+        raw.reconnect_func = Some(reconnect);
+        raw.close_func = Some(close);
+        raw.recvmmsg_func = Some(recvmmsg);
+        raw.send_func = Some(send);
+        raw.get_so_rcvbuf_func = Some(get_so_rcvbuf);
+        raw.bind_addr_and_port_func = Some(bind_addr_and_port);
+        raw.poller_init_func = Some(poller_init);
+        raw.poller_close_func = Some(poller_close);
+        raw.poller_add_func = Some(poller_add);
+        raw.poller_remove_func = Some(poller_remove);
+        raw.poller_poll_func = Some(poller_poll);
+        RawBindings(raw)
+    })
+}
+
+pub(crate) fn set_bindings(
+    context: *mut aeron_driver::aeron_driver_context_t,
+    bindings: Arc<dyn UdpChannelTransportBindings>,
+) -> i32 {
+    BINDINGS.insert(context, bindings);
+    unsafe {
+        aeron_driver::aeron_driver_context_set_udp_channel_transport_bindings(
+            context,
+            &raw_bindings().0,
+        )
+    }
+}
+
+pub(crate) fn remove_bindings(context: *mut aeron_driver::aeron_driver_context_t) {
+    BINDINGS.remove(context);
+}
+
+unsafe extern "C" fn init(
+    transport: *mut aeron_driver::aeron_udp_channel_transport_t,
+    bind_addr: *mut aeron_driver::sockaddr_storage,
+    multicast_if_addr: *mut aeron_driver::sockaddr_storage,
+    connect_addr: *mut aeron_driver::sockaddr_storage,
+    _params: *mut aeron_driver::aeron_udp_channel_transport_params_t,
+    context: *mut aeron_driver::aeron_driver_context_t,
+    _affinity: aeron_driver::aeron_udp_channel_transport_affinity_t,
+) -> i32 {
+    let Some(bindings) = BINDINGS.get(context) else {
+        return -1;
+    };
+    let addresses = TransportAddresses {
+        bind: sockaddr::from_raw(bind_addr),
+        connect: sockaddr::from_raw(connect_addr),
+        multicast_interface: sockaddr::from_raw(multicast_if_addr),
+    };
+    match bindings.init(&addresses) {
+        Ok(rust_transport) => {
+            (*transport).fd = -1;
+            (*transport).recv_fd = -1;
+            let state = TransportState {
+                transport: rust_transport,
+                connect: addresses.connect,
+            };
+            (*transport).bindings_clientd = Box::into_raw(Box::new(Mutex::new(state))) as *mut _;
+            0
+        }
+        Err(error) => {
+            eprintln!("failed to initialise udp channel transport {addresses:?}: {error}");
+            -1
+        }
+    }
+}
+
+unsafe extern "C" fn reconnect(
+    transport: *mut aeron_driver::aeron_udp_channel_transport_t,
+    connect_addr: *mut aeron_driver::sockaddr_storage,
+) -> i32 {
+    let Some(state) = transport_state(transport) else {
+        return -1;
+    };
+    state.lock().unwrap().connect = sockaddr::from_raw(connect_addr);
+    0
+}
+
+unsafe extern "C" fn close(transport: *mut aeron_driver::aeron_udp_channel_transport_t) -> i32 {
+    let state = (*transport).bindings_clientd as *mut Mutex<TransportState>;
+    if !state.is_null() {
+        let state = Box::from_raw(state);
+        state.lock().unwrap().transport.close();
+        (*transport).bindings_clientd = ptr::null_mut();
+    }
+    0
+}
+
+unsafe extern "C" fn recvmmsg(
+    transport: *mut aeron_driver::aeron_udp_channel_transport_t,
+    msgvec: *mut aeron_driver::mmsghdr,
+    vlen: usize,
+    bytes_rcved: *mut i64,
+    recv_func: aeron_driver::aeron_udp_transport_recv_func_t,
+    clientd: *mut std::ffi::c_void,
+) -> i32 {
+    let (Some(state), Some(recv_func)) = (transport_state(transport), recv_func) else {
+        return -1;
+    };
+
+    // Received before dispatching, as the driver may send on this transport in recv_func
+    let mut datagrams = Vec::new();
+    let result = state
+        .lock()
+        .unwrap()
+        .transport
+        .recvmmsg(vlen, &mut |datagram, source| {
+            datagrams.push((datagram.to_vec(), source))
+        });
+    if result.is_err() {
+        return -1;
+    }
+
+    let messages = slice::from_raw_parts_mut(msgvec, vlen);
+    let mut received = 0;
+    for (datagram, source) in datagrams {
+        let Some(message) = messages.get_mut(received) else {
+            break;
+        };
+        let iov = &*message.msg_hdr.msg_iov;
+        if datagram.len() > iov.iov_len {
+            OVERSIZED_DATAGRAMS.fetch_add(1, Ordering::Relaxed);
+            continue;
+        }
+        let length = datagram.len();
+        ptr::copy_nonoverlapping(datagram.as_ptr(), iov.iov_base as *mut u8, length);
+        sockaddr::write(source, message.msg_hdr.msg_name);
+        message.msg_hdr.msg_namelen = sockaddr::raw_len(&source) as _;
+        message.msg_len = length as _;
+        *bytes_rcved += length as i64;
+
+        recv_func(
+            (*transport).data_paths,
+            transport,
+            clientd,
+            (*transport).dispatch_clientd,
+            (*transport).destination_clientd,
+            iov.iov_base as *mut u8,
+            length,
+            message.msg_hdr.msg_name as *mut aeron_driver::sockaddr_storage,
+            ptr::null_mut(),
+        );
+        received += 1;
+    }
+    received as i32
+}
+
+unsafe extern "C" fn send(
+    transport: *mut aeron_driver::aeron_udp_channel_transport_t,
+    address: *mut aeron_driver::sockaddr_storage,
+    iov: *mut aeron_driver::iovec,
+    iov_length: usize,
+    bytes_sent: *mut i64,
+) -> i32 {
+    let Some(state) = transport_state(transport) else {
+        return -1;
+    };
+    let mut state = state.lock().unwrap();
+    let Some(destination) = sockaddr::from_raw(address).or(state.connect) else {
+        return -1;
+    };
+
+    // Each iovec is a separate datagram
+    let datagrams: Vec<&[u8]> = slice::from_raw_parts(iov, iov_length)
+        .iter()
+        .map(|iov| slice::from_raw_parts(iov.iov_base as *const u8, iov.iov_len))
+        .collect();
+    match state.transport.sendmmsg(destination, &datagrams) {
+        Ok(sent) => {
+            // Never trust the transport to report more than it was given
+            let sent = sent.min(datagrams.len());
+            *bytes_sent = datagrams[..sent]
+                .iter()
+                .map(|datagram| datagram.len() as i64)
+                .sum();
+            sent as i32
+        }
+        Err(_) => -1,
+    }
+}
+
+unsafe extern "C" fn get_so_rcvbuf(
+    transport: *mut aeron_driver::aeron_udp_channel_transport_t,
+    so_rcvbuf: *mut usize,
+) -> i32 {
+    let Some(state) = transport_state(transport) else {
+        return -1;
+    };
+    *so_rcvbuf = state.lock().unwrap().transport.so_rcvbuf();
+    0
+}
+
+unsafe extern "C" fn bind_addr_and_port(
+    transport: *mut aeron_driver::aeron_udp_channel_transport_t,
+    buffer: *mut c_char,
+    length: usize,
+) -> i32 {
+    let (Some(state), true) = (transport_state(transport), length > 0) else {
+        return -1;
+    };
+    // Written as an empty string when the transport is not bound
+    let local_addr = state
+        .lock()
+        .unwrap()
+        .transport
+        .local_addr()
+        .map(|local_addr| local_addr.to_string())
+        .unwrap_or_default();
+    let written = local_addr.len().min(length - 1);
+    ptr::copy_nonoverlapping(local_addr.as_ptr() as *const c_char, buffer, written);
+    *buffer.add(written) = 0;
+    written as i32
+}
+
+unsafe extern "C" fn poller_init(
+    poller: *mut aeron_driver::aeron_udp_transport_poller_t,
+    _context: *mut aeron_driver::aeron_driver_context_t,
+    _affinity: aeron_driver::aeron_udp_channel_transport_affinity_t,
+) -> i32 {
+    (*poller).bindings_clientd = Box::into_raw(Box::new(PollerTransports::new())) as *mut _;
+    0
+}
+
+unsafe extern "C" fn poller_close(poller: *mut aeron_driver::aeron_udp_transport_poller_t) -> i32 {
+    let transports = (*poller).bindings_clientd as *mut PollerTransports;
+    if !transports.is_null() {
+        drop(Box::from_raw(transports));
+        (*poller).bindings_clientd = ptr::null_mut();
+    }
+    0
+}
+
+unsafe extern "C" fn poller_add(
+    poller: *mut aeron_driver::aeron_udp_transport_poller_t,
+    transport: *mut aeron_driver::aeron_udp_channel_transport_t,
+) -> i32 {
+    let Some(transports) = poller_transports(poller) else {
+        return -1;
+    };
+    transports.push(transport);
+    0
+}
+
+unsafe extern "C" fn poller_remove(
+    poller: *mut aeron_driver::aeron_udp_transport_poller_t,
+    transport: *mut aeron_driver::aeron_udp_channel_transport_t,
+) -> i32 {
+    let Some(transports) = poller_transports(poller) else {
+        return -1;
+    };
+    transports.retain(|added| *added != transport);
+    0
+}
+
+unsafe extern "C" fn poller_poll(
+    poller: *mut aeron_driver::aeron_udp_transport_poller_t,
+    msgvec: *mut aeron_driver::mmsghdr,
+    vlen: usize,
+    bytes_rcved: *mut i64,
+    recv_func: aeron_driver::aeron_udp_transport_recv_func_t,
+    recvmmsg_func: aeron_driver::aeron_udp_channel_transport_recvmmsg_func_t,
+    clientd: *mut std::ffi::c_void,
+) -> i32 {
+    let (Some(transports), Some(recvmmsg_func)) = (poller_transports(poller), recvmmsg_func) else {
+        return -1;
+    };
+
+    // Copied so receiving can add or remove transports of this poller
+    let transports = transports.clone();
+    let mut work_count = 0;
+    for transport in transports {
+        let result = recvmmsg_func(transport, msgvec, vlen, bytes_rcved, recv_func, clientd);
+        if result < 0 {
+            return result;
+        }
+        work_count += result;
+    }
+    work_count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    fn loopback(port: u16) -> TransportAddresses {
+        TransportAddresses {
+            bind: Some((Ipv4Addr::LOCALHOST, port).into()),
+            connect: None,
+            multicast_interface: None,
+        }
+    }
+
+    #[test]
+    fn udp_socket_bindings_exchange_datagrams() {
+        let mut receiver = UdpSocketBindings.init(&loopback(0)).unwrap();
+        let mut sender = UdpSocketBindings.init(&loopback(0)).unwrap();
+        let destination = receiver.local_addr().unwrap();
+        assert_ne!(destination.port(), 0);
+
+        assert_eq!(sender.sendmmsg(destination, &[b"one", b"two"]).unwrap(), 2);
+        let mut received = Vec::new();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while received.len() < 2 && Instant::now() < deadline {
+            receiver
+                .recvmmsg(8, &mut |datagram, source| {
+                    assert_eq!(Some(source), sender.local_addr());
+                    received.push(datagram.to_vec());
+                })
+                .unwrap();
+        }
+        assert_eq!(received, vec![b"one".to_vec(), b"two".to_vec()]);
+    }
+}
@@ -0,0 +1,129 @@
+// Helpers shared by the integration tests. Each test binary uses only some of them.
+#![allow(dead_code)]
+
+use aeron_rs_media_driver::client::{Aeron, AeronPublication, AeronSubscription};
+use aeron_rs_media_driver::test_support::TestMediaDriver;
+use std::ops::Range;
+use std::time::{Duration, Instant};
+
+pub const TIMEOUT: Duration = Duration::from_secs(10);
+pub const STREAM_ID: i32 = 1001;
+
+pub fn connect(driver: &TestMediaDriver) -> Aeron {
+    let aeron = Aeron::new(&driver.client_context().unwrap()).unwrap();
+    aeron.start().unwrap();
+    aeron
+}
+
+pub fn await_condition(what: &str, mut condition: impl FnMut() -> bool) {
+    let deadline = Instant::now() + TIMEOUT;
+    while !condition() {
+        assert!(Instant::now() < deadline, "timed out waiting for {what}");
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
+
+pub fn await_connected(publication: &AeronPublication, subscription: &AeronSubscription) {
+    await_condition("publication and subscription to connect", || {
+        publication.is_connected() && subscription.is_connected()
+    });
+}
+
+/// Subscribes on `{host}:0` so the driver binds a free port, returning the subscription and
+/// the channel to publish to it on. Tests run in parallel, so fixed ports would collide.
+pub fn add_udp_subscription(aeron: &Aeron, host: &str) -> (AeronSubscription, String) {
+    let channel = format!("aeron:udp?endpoint={host}:0");
+    let subscription = aeron
+        .add_subscription(&channel, STREAM_ID, TIMEOUT)
+        .unwrap();
+    let mut resolved = None;
+    await_condition("subscription endpoint to be bound", || {
+        resolved = subscription.try_resolve_channel_endpoint_port().unwrap();
+        resolved.is_some()
+    });
+
+    let port = endpoint_port(&resolved.unwrap());
+    (subscription, format!("aeron:udp?endpoint={host}:{port}"))
+}
+
+/// A UDP subscription on a free port and a publication to it.
+pub fn add_udp_pair(aeron: &Aeron, host: &str) -> (AeronPublication, AeronSubscription) {
+    let (subscription, channel) = add_udp_subscription(aeron, host);
+    let publication = aeron.add_publication(&channel, STREAM_ID, TIMEOUT).unwrap();
+    (publication, subscription)
+}
+
+/// The port of the `endpoint` parameter of a channel.
+fn endpoint_port(channel: &str) -> u16 {
+    channel
+        .split(['?', '|'])
+        .find_map(|param| param.strip_prefix("endpoint="))
+        .and_then(|endpoint| endpoint.rsplit_once(':'))
+        .and_then(|(_, port)| port.parse().ok())
+        .unwrap_or_else(|| panic!("no endpoint port in {channel}"))
+}
+
+/// Offers `buffer`, retrying while the publication is back pressured or not connected.
+pub fn offer(publication: &AeronPublication, buffer: &[u8]) {
+    await_condition("offer to succeed", || match publication.offer(buffer) {
+        Ok(_) => true,
+        Err(error) if error.is_retryable() => false,
+        Err(error) => panic!("offer failed: {error}"),
+    });
+}
+
+/// Offers each number in `messages` as a little endian `u32`.
+pub fn offer_all(publication: &AeronPublication, messages: Range<u32>) {
+    for i in messages {
+        offer(publication, &i.to_le_bytes());
+    }
+}
+
+/// Polls until at least `count` messages have arrived.
+pub fn receive(subscription: &AeronSubscription, count: usize) -> Vec<Vec<u8>> {
+    let mut messages = Vec::new();
+    await_condition("messages to arrive", || {
+        subscription
+            .poll(|buffer| messages.push(buffer.to_vec()), 10)
+            .unwrap();
+        messages.len() >= count
+    });
+    messages
+}
+
+/// Polls `u32` messages into `received` until it holds at least `count`.
+pub fn receive_u32s(subscription: &AeronSubscription, received: &mut Vec<u32>, count: usize) {
+    await_condition("messages to arrive", || {
+        subscription
+            .poll(
+                |buffer| received.push(u32::from_le_bytes(buffer.try_into().unwrap())),
+                10,
+            )
+            .unwrap();
+        received.len() >= count
+    });
+}
+
+/// Sends `count` messages over a UDP channel to `host` and checks they all arrive in order.
+pub fn exchange(driver: &TestMediaDriver, host: &str, count: u32) {
+    let aeron = connect(driver);
+    let (publication, subscription) = add_udp_pair(&aeron, host);
+    await_connected(&publication, &subscription);
+
+    // Offers and polls in turn, so a small window cannot hold the publication back forever
+    let mut sent = 0u32;
+    let mut received = Vec::new();
+    await_condition("messages to arrive", || {
+        if sent < count && publication.offer(&sent.to_le_bytes()).is_ok() {
+            sent += 1;
+        }
+        subscription
+            .poll(
+                |buffer| received.push(u32::from_le_bytes(buffer.try_into().unwrap())),
+                10,
+            )
+            .unwrap();
+        received.len() == count as usize
+    });
+    assert_eq!(received, (0..count).collect::<Vec<_>>());
+}
@@ -0,0 +1,36 @@
+mod common;
+
+use aeron_rs_media_driver::interceptor::{ImpairedBindings, Impairment, LossInterceptor};
+use aeron_rs_media_driver::test_support::TestMediaDriver;
+use aeron_rs_media_driver::transport::UdpSocketBindings;
+use common::{exchange, STREAM_ID};
+use std::time::Duration;
+
+// Kept apart from the other integration tests because the loss parameters are process-wide.
+
+#[test]
+fn data_loss_is_recovered_by_retransmission() {
+    let driver = TestMediaDriver::launch_with(|context| {
+        context.set_loss_interceptor(&LossInterceptor::data_loss(0.2, 7))
+    })
+    .unwrap();
+    exchange(&driver, "localhost", 100);
+}
+
+#[test]
+fn impaired_udp_sockets_lose_and_delay_one_stream() {
+    let bindings = ImpairedBindings::new(
+        UdpSocketBindings,
+        Impairment::data_loss(0.2, 7)
+            .with_stream_id(STREAM_ID)
+            .with_delay(Duration::from_millis(2)),
+    );
+    let driver_bindings = bindings.clone();
+    let driver = TestMediaDriver::launch_with(move |context| {
+        context.set_udp_channel_transport_bindings(driver_bindings)
+    })
+    .unwrap();
+
+    exchange(&driver, "localhost", 100);
+    assert!(bindings.dropped_datagrams() > 0);
+}
@@ -0,0 +1,240 @@
+mod common;
+
+use aeron_rs_media_driver::client::{
+    Aeron, AeronPublication, AeronSubscription, ControlledPollAction,
+};
+use aeron_rs_media_driver::cnc::{check_driver, DriverStatus};
+use aeron_rs_media_driver::codec::{Decode, DecodeError, Encode};
+use aeron_rs_media_driver::test_support::TestMediaDriver;
+use common::{
+    add_udp_pair, add_udp_subscription, await_condition, await_connected, connect, offer, receive,
+    STREAM_ID, TIMEOUT,
+};
+use std::panic::{self, AssertUnwindSafe};
+
+const IPC: &str = "aeron:ipc";
+
+fn add_ipc_pair(aeron: &Aeron) -> (AeronPublication, AeronSubscription) {
+    let subscription = aeron.add_subscription(IPC, STREAM_ID, TIMEOUT).unwrap();
+    let publication = aeron.add_publication(IPC, STREAM_ID, TIMEOUT).unwrap();
+    (publication, subscription)
+}
+
+fn add_loopback_pair(aeron: &Aeron) -> (AeronPublication, AeronSubscription) {
+    add_udp_pair(aeron, "localhost")
+}
+
+fn round_trip(add_pair: fn(&Aeron) -> (AeronPublication, AeronSubscription)) {
+    let driver = TestMediaDriver::launch().unwrap();
+    let aeron = connect(&driver);
+    let (publication, subscription) = add_pair(&aeron);
+    await_connected(&publication, &subscription);
+
+    for i in 0..10u8 {
+        offer(&publication, &[i; 16]);
+    }
+
+    let messages = receive(&subscription, 10);
+    let expected: Vec<_> = (0..10u8).map(|i| vec![i; 16]).collect();
+    assert_eq!(messages, expected);
+}
+
+#[test]
+fn ipc_round_trip() {
+    round_trip(add_ipc_pair);
+}
+
+#[test]
+fn udp_loopback_round_trip() {
+    round_trip(add_loopback_pair);
+}
+
+#[test]
+fn fragmented_message_is_reassembled() {
+    let driver = TestMediaDriver::launch().unwrap();
+    let aeron = connect(&driver);
+    let (publication, subscription) = add_loopback_pair(&aeron);
+    await_connected(&publication, &subscription);
+
+    let length = publication.max_payload_length() * 3 + 17;
+    let message: Vec<u8> = (0..length).map(|i| (i % 251) as u8).collect();
+    offer(&publication, &message);
+
+    let messages = receive(&subscription, 1);
+    assert_eq!(messages, vec![message]);
+}
+
+struct Sample {
+    sequence: u64,
+    value: i32,
+}
+
+impl Encode for Sample {
+    fn encoded_length(&self) -> usize {
+        12
+    }
+
+    fn encode(&self, buffer: &mut [u8]) {
+        self.sequence.encode(&mut buffer[0..8]);
+        self.value.encode(&mut buffer[8..12]);
+    }
+}
+
+impl Decode<'_> for Sample {
+    fn decode(buffer: &[u8]) -> Result<Self, DecodeError> {
+        DecodeError::check_length(buffer, 12)?;
+        Ok(Sample {
+            sequence: u64::decode(&buffer[0..8])?,
+            value: i32::decode(&buffer[8..12])?,
+        })
+    }
+}
+
+#[test]
+fn try_claim_and_typed_messages() {
+    let driver = TestMediaDriver::launch().unwrap();
+    let aeron = connect(&driver);
+    let subscription = aeron.add_subscription(IPC, STREAM_ID, TIMEOUT).unwrap();
+    let publication = aeron.add_publication(IPC, STREAM_ID, TIMEOUT).unwrap();
+    await_connected(&publication, &subscription);
+
+    assert!(publication
+        .try_claim(publication.max_payload_length() + 1, |_| {})
+        .is_err());
+    // A panicking fill aborts its claim, which the subscriber never sees
+    let claim = panic::catch_unwind(AssertUnwindSafe(|| {
+        publication.try_claim(4, |_| panic!("fill failed"))
+    }));
+    assert!(claim.is_err());
+
+    publication
+        .try_claim(4, |buffer| buffer.copy_from_slice(b"ping"))
+        .unwrap();
+    assert_eq!(receive(&subscription, 1), vec![b"ping".to_vec()]);
+
+    for sequence in 0..5 {
+        publication
+            .offer_msg(&Sample {
+                sequence,
+                value: -(sequence as i32),
+            })
+            .unwrap();
+    }
+
+    let mut samples = Vec::new();
+    await_condition("typed messages to arrive", || {
+        subscription
+            .poll_msgs(
+                |sample: Sample| samples.push((sample.sequence, sample.value)),
+                10,
+            )
+            .unwrap();
+        samples.len() >= 5
+    });
+    assert_eq!(samples, vec![(0, 0), (1, -1), (2, -2), (3, -3), (4, -4)]);
+}
+
+#[test]
+fn poll_handler_panic_is_resumed_after_poll() {
+    let driver = TestMediaDriver::launch().unwrap();
+    let aeron = connect(&driver);
+    let (publication, subscription) = add_ipc_pair(&aeron);
+    await_connected(&publication, &subscription);
+
+    offer(&publication, b"first");
+    await_condition("poll handler to panic", || {
+        panic::catch_unwind(AssertUnwindSafe(|| {
+            subscription.poll(|_| panic!("handler failed"), 10)
+        }))
+        .is_err()
+    });
+
+    offer(&publication, b"second");
+    assert_eq!(receive(&subscription, 1), vec![b"second".to_vec()]);
+}
+
+#[test]
+fn controlled_poll_abort_redelivers() {
+    let driver = TestMediaDriver::launch().unwrap();
+    let aeron = connect(&driver);
+    let subscription = aeron.add_subscription(IPC, STREAM_ID, TIMEOUT).unwrap();
+    let publication = aeron.add_publication(IPC, STREAM_ID, TIMEOUT).unwrap();
+    await_connected(&publication, &subscription);
+
+    offer(&publication, b"first");
+    offer(&publication, b"second");
+
+    let mut aborted = 0;
+    await_condition("first message to be aborted", || {
+        subscription
+            .controlled_poll(
+                |_| {
+                    aborted += 1;
+                    ControlledPollAction::Abort
+                },
+                10,
+            )
+            .unwrap();
+        aborted > 0
+    });
+
+    let mut messages = Vec::new();
+    await_condition("messages to be redelivered", || {
+        subscription
+            .controlled_poll(
+                |buffer| {
+                    messages.push(buffer.to_vec());
+                    ControlledPollAction::Break
+                },
+                10,
+            )
+            .unwrap();
+        messages.len() >= 2
+    });
+    assert_eq!(messages, vec![b"first".to_vec(), b"second".to_vec()]);
+}
+
+#[test]
+fn image_becomes_unavailable_when_publication_closes() {
+    let driver = TestMediaDriver::launch().unwrap();
+    let aeron = connect(&driver);
+    let (subscription, channel) = add_udp_subscription(&aeron, "localhost");
+    assert_eq!(subscription.image_count(), 0);
+
+    let publication = aeron.add_publication(&channel, STREAM_ID, TIMEOUT).unwrap();
+    await_condition("image to become available", || {
+        subscription.image_count() == 1
+    });
+
+    drop(publication);
+    await_condition("image to become unavailable", || {
+        subscription.image_count() == 0
+    });
+    assert!(!subscription.is_connected());
+}
+
+#[test]
+fn driver_restart_in_same_dir() {
+    let driver = TestMediaDriver::launch().unwrap();
+    let dir = driver.dir().to_string();
+    {
+        let aeron = connect(&driver);
+        let subscription = aeron.add_subscription(IPC, STREAM_ID, TIMEOUT).unwrap();
+        let publication = aeron.add_publication(IPC, STREAM_ID, TIMEOUT).unwrap();
+        await_connected(&publication, &subscription);
+    }
+    drop(driver);
+    assert_eq!(check_driver(&dir, TIMEOUT), DriverStatus::NoCncFile);
+
+    // Also checks that `launch_with` tracks, and waits on, the dir set by the closure
+    let restarted = TestMediaDriver::launch_with(|context| context.set_dir(&dir)).unwrap();
+    assert_eq!(restarted.dir(), dir);
+    assert!(check_driver(&dir, TIMEOUT).is_active());
+
+    let aeron = connect(&restarted);
+    let subscription = aeron.add_subscription(IPC, STREAM_ID, TIMEOUT).unwrap();
+    let publication = aeron.add_publication(IPC, STREAM_ID, TIMEOUT).unwrap();
+    await_connected(&publication, &subscription);
+    offer(&publication, b"after restart");
+    assert_eq!(receive(&subscription, 1), vec![b"after restart".to_vec()]);
+}
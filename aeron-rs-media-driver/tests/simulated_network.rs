@@ -0,0 +1,58 @@
+mod common;
+
+use aeron_rs_media_driver::simulated_network::SimulatedNetwork;
+use aeron_rs_media_driver::test_support::TestMediaDriver;
+use common::{await_connected, connect, offer_all, receive_u32s, STREAM_ID, TIMEOUT};
+use std::net::{IpAddr, Ipv4Addr};
+use std::time::Duration;
+
+const CHANNEL: &str = "aeron:udp?endpoint=10.0.0.2:40456";
+const HOST_A: IpAddr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+const HOST_B: IpAddr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+
+fn launch_on(network: &SimulatedNetwork, host: IpAddr) -> TestMediaDriver {
+    let bindings = network.bindings(host);
+    TestMediaDriver::launch_with(move |context| {
+        context.set_udp_channel_transport_bindings(bindings)
+    })
+    .unwrap()
+}
+
+#[test]
+fn drivers_exchange_over_simulated_network() {
+    let network = SimulatedNetwork::new();
+    network.set_reordering(0.2, 42);
+    let driver_a = launch_on(&network, HOST_A);
+    let driver_b = launch_on(&network, HOST_B);
+    let aeron_a = connect(&driver_a);
+    let aeron_b = connect(&driver_b);
+
+    let subscription = aeron_b
+        .add_subscription(CHANNEL, STREAM_ID, TIMEOUT)
+        .unwrap();
+    let publication = aeron_a
+        .add_publication(CHANNEL, STREAM_ID, TIMEOUT)
+        .unwrap();
+    await_connected(&publication, &subscription);
+
+    let mut received = Vec::new();
+    offer_all(&publication, 0..100);
+    receive_u32s(&subscription, &mut received, 100);
+
+    // Data sent during a partition is retransmitted once it heals
+    network.partition(HOST_A, HOST_B);
+    offer_all(&publication, 100..110);
+    std::thread::sleep(Duration::from_millis(200));
+    subscription
+        .poll(
+            |buffer| received.push(u32::from_le_bytes(buffer.try_into().unwrap())),
+            10,
+        )
+        .unwrap();
+    assert_eq!(received, (0..100).collect::<Vec<_>>());
+    assert!(network.dropped_datagrams() > 0);
+    network.heal(HOST_A, HOST_B);
+
+    receive_u32s(&subscription, &mut received, 110);
+    assert_eq!(received, (0..110).collect::<Vec<_>>());
+}
@@ -0,0 +1,143 @@
+mod common;
+
+use aeron_rs_media_driver::congestion_control::{
+    CongestionControlStrategy, TrackRebuild, WindowUpdate,
+};
+use aeron_rs_media_driver::flow_control::{FlowControlStrategy, StatusMessage};
+use aeron_rs_media_driver::name_resolver::{ResolverCache, StaticNameResolver};
+use aeron_rs_media_driver::test_support::TestMediaDriver;
+use common::{await_condition, exchange};
+use std::net::{IpAddr, Ipv4Addr, UdpSocket};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Lets the sender run a fixed distance ahead of the receiver's position.
+struct FixedWindow {
+    window: i64,
+    status_messages: Arc<AtomicUsize>,
+}
+
+impl FlowControlStrategy for FixedWindow {
+    fn on_status_message(
+        &mut self,
+        status: &StatusMessage,
+        sender_limit: i64,
+        _now_ns: i64,
+    ) -> i64 {
+        self.status_messages.fetch_add(1, Ordering::Relaxed);
+        sender_limit.max(status.position + self.window)
+    }
+}
+
+#[test]
+fn custom_unicast_flow_control() {
+    let status_messages = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&status_messages);
+    let driver = TestMediaDriver::launch_with(move |context| {
+        context.set_unicast_flow_control(move |_| {
+            Box::new(FixedWindow {
+                window: 4096,
+                status_messages: Arc::clone(&counter),
+            })
+        })
+    })
+    .unwrap();
+
+    exchange(&driver, "localhost", 1000);
+    assert!(status_messages.load(Ordering::Relaxed) > 0);
+}
+
+/// Advertises a fixed window and counts the rebuilds and status messages the receiver
+/// reports.
+struct CountingWindow {
+    rebuilds: Arc<AtomicUsize>,
+    status_messages: Arc<AtomicUsize>,
+}
+
+impl CongestionControlStrategy for CountingWindow {
+    fn initial_window_length(&self) -> i32 {
+        32 * 1024
+    }
+
+    fn on_track_rebuild(&mut self, _rebuild: &TrackRebuild, _now_ns: i64) -> WindowUpdate {
+        self.rebuilds.fetch_add(1, Ordering::Relaxed);
+        WindowUpdate {
+            window_length: self.initial_window_length(),
+            force_status_message: false,
+        }
+    }
+
+    fn on_status_message(&mut self, _now_ns: i64, _position: i64, _window_length: i32) {
+        self.status_messages.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[test]
+fn custom_congestion_control() {
+    let rebuilds = Arc::new(AtomicUsize::new(0));
+    let status_messages = Arc::new(AtomicUsize::new(0));
+    let (rebuild_counter, status_counter) = (Arc::clone(&rebuilds), Arc::clone(&status_messages));
+    let driver = TestMediaDriver::launch_with(move |context| {
+        context.set_congestion_control(move |_| {
+            Box::new(CountingWindow {
+                rebuilds: Arc::clone(&rebuild_counter),
+                status_messages: Arc::clone(&status_counter),
+            })
+        })
+    })
+    .unwrap();
+
+    exchange(&driver, "localhost", 1000);
+    assert!(rebuilds.load(Ordering::Relaxed) > 0);
+    assert!(status_messages.load(Ordering::Relaxed) > 0);
+}
+
+#[test]
+fn static_name_resolver_maps_logical_names() {
+    let driver = TestMediaDriver::launch_with(|context| {
+        context.set_name_resolver(|| {
+            Box::new(
+                StaticNameResolver::new().with_entry("service-a", IpAddr::V4(Ipv4Addr::LOCALHOST)),
+            )
+        })
+    })
+    .unwrap();
+
+    exchange(&driver, "service-a", 100);
+}
+
+/// A port that was free a moment ago, for the resolver interfaces the drivers must know
+/// each other by.
+fn free_udp_port() -> u16 {
+    UdpSocket::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+#[test]
+fn resolver_cache_holds_the_names_of_neighbors() {
+    let (port_a, port_b) = (free_udp_port(), free_udp_port());
+    let cache = ResolverCache::new();
+    let observed = cache.clone();
+    let _driver_a = TestMediaDriver::launch_with(move |context| {
+        context.set_resolver_name("driver-a")?;
+        context.set_resolver_interface(&format!("127.0.0.1:{port_a}"))?;
+        context.set_resolver_cache(&observed)
+    })
+    .unwrap();
+    let _driver_b = TestMediaDriver::launch_with(move |context| {
+        context.set_resolver_name("driver-b")?;
+        context.set_resolver_interface(&format!("127.0.0.1:{port_b}"))?;
+        context.set_resolver_bootstrap_neighbor(&format!("127.0.0.1:{port_a}"))
+    })
+    .unwrap();
+
+    await_condition("driver-b in the resolver cache of driver-a", || {
+        cache
+            .entries()
+            .iter()
+            .any(|entry| entry.name == "driver-b" && entry.address.port() == port_b)
+    });
+}
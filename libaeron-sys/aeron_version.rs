@@ -0,0 +1,106 @@
+// Version checks of an installed Aeron, shared by the build scripts of both crates.
+// `libaeron_driver-sys` links this file, so each crate packages its own copy.
+
+/// Reads the version of an installed Aeron from the `AERON_VERSION_*` defines of `header`
+/// or, as release headers do not carry them, from the version string compiled into
+/// `library`.
+fn installed_version(
+    include_paths: &[PathBuf],
+    lib_dirs: &[PathBuf],
+    header: &str,
+    library: &str,
+) -> Option<String> {
+    header_version(include_paths, header).or_else(|| library_version(lib_dirs, library))
+}
+
+/// Looks for `header` in each include path and in its `aeron`/`aeronmd` subdirectory, where
+/// installations put the client and driver headers.
+fn header_version(include_paths: &[PathBuf], header: &str) -> Option<String> {
+    let headers = include_paths.iter().flat_map(|path| {
+        [
+            path.join(header),
+            path.join("aeron").join(header),
+            path.join("aeronmd").join(header),
+        ]
+    });
+    for header in headers {
+        let Ok(source) = fs::read_to_string(&header) else {
+            continue;
+        };
+        let define = |name: &str| {
+            source.lines().find_map(|line| {
+                let mut words = line.split_whitespace();
+                if words.next()? != "#define" || words.next()? != name {
+                    return None;
+                }
+                words.next()?.parse::<u32>().ok()
+            })
+        };
+        if let (Some(major), Some(minor), Some(patch)) = (
+            define("AERON_VERSION_MAJOR"),
+            define("AERON_VERSION_MINOR"),
+            define("AERON_VERSION_PATCH"),
+        ) {
+            return Some(format!("{major}.{minor}.{patch}"));
+        }
+    }
+    None
+}
+
+/// The files of library `name`, static or shared, found in `lib_dirs`.
+fn library_files<'a>(lib_dirs: &'a [PathBuf], name: &str) -> impl Iterator<Item = PathBuf> + 'a {
+    let file_names = [
+        format!("lib{name}.so"),
+        format!("lib{name}.a"),
+        format!("lib{name}.dylib"),
+        format!("{name}.lib"),
+        format!("{name}.dll"),
+    ];
+    lib_dirs
+        .iter()
+        .flat_map(move |lib_dir| file_names.clone().map(|file_name| lib_dir.join(file_name)))
+        .filter(|library| library.is_file())
+}
+
+fn library_version(lib_dirs: &[PathBuf], name: &str) -> Option<String> {
+    library_files(lib_dirs, name)
+        .filter_map(|library| fs::read(library).ok())
+        .find_map(|library| embedded_version(&library))
+}
+
+/// Finds the release in the `version=1.46.5 commit=...` string Aeron compiles into its
+/// libraries.
+fn embedded_version(library: &[u8]) -> Option<String> {
+    const MARKER: &[u8] = b"version=";
+    library
+        .windows(MARKER.len())
+        .enumerate()
+        .filter(|(_, window)| *window == MARKER)
+        .find_map(|(start, _)| {
+            let version: String = library[start + MARKER.len()..]
+                .iter()
+                .take_while(|&&byte| byte.is_ascii_digit() || byte == b'.')
+                .map(|&byte| byte as char)
+                .collect();
+            let parts: Vec<&str> = version.split('.').collect();
+            (parts.len() == 3 && parts.iter().all(|part| !part.is_empty())).then_some(version)
+        })
+}
+
+/// The bindings are generated from one Aeron release, and even patch releases may change
+/// the C structs they describe, so the installed version has to match the crate version.
+fn check_version(installed: Option<&str>) {
+    let expected = env!("CARGO_PKG_VERSION");
+    let Some(installed) = installed else {
+        panic!(
+            "cannot determine the version of the installed Aeron for {}; install Aeron {expected} or build the bundled submodule instead",
+            env!("CARGO_PKG_NAME")
+        );
+    };
+    if installed != expected {
+        panic!(
+            "installed Aeron {installed} does not match version {expected} of {}, whose bindings are only valid for that release; install Aeron {expected} or build the bundled submodule instead",
+            env!("CARGO_PKG_NAME")
+        );
+    }
+}
@@ -2,8 +2,33 @@
 #![allow(non_camel_case_types)]
 #![allow(non_snake_case)]
 #![allow(clippy::all)]
+#[cfg(feature = "generate-bindings")]
 include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
 
+// Checked-in bindings, so builds for these targets do not need libclang
+#[cfg(all(
+    not(feature = "generate-bindings"),
+    target_arch = "x86_64",
+    target_os = "linux"
+))]
+include!("../bindings/x86_64-linux.rs");
+#[cfg(all(
+    not(feature = "generate-bindings"),
+    target_arch = "aarch64",
+    target_os = "linux"
+))]
+include!("../bindings/aarch64-linux.rs");
+#[cfg(not(any(
+    feature = "generate-bindings",
+    all(
+        target_os = "linux",
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    )
+)))]
+compile_error!(
+    "no pre-generated bindings for this target; enable the `generate-bindings` feature to generate them with bindgen"
+);
+
 #[cfg(test)]
 mod tests {
 
@@ -17,4 +42,55 @@ mod tests {
         let cargo_version = env!("CARGO_PKG_VERSION");
         assert_eq!(aeron_version, cargo_version);
     }
+
+    #[cfg(feature = "generate-bindings")]
+    fn pregenerated_bindings_path() -> std::path::PathBuf {
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("bindings")
+            .join(format!(
+                "{}-{}.rs",
+                std::env::consts::ARCH,
+                std::env::consts::OS
+            ))
+    }
+
+    /// The checked-in bindings of this target must match what bindgen generates from the
+    /// headers. Refresh them with `update_pregenerated_bindings`.
+    #[cfg(all(
+        feature = "generate-bindings",
+        target_os = "linux",
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    ))]
+    #[test]
+    fn pregenerated_bindings_match() {
+        let pregenerated = pregenerated_bindings_path();
+        let expected = std::fs::read_to_string(&pregenerated).unwrap_or_else(|error| {
+            panic!(
+                "no pre-generated bindings at {}: {error}",
+                pregenerated.display()
+            )
+        });
+        let generated = include_str!(concat!(env!("OUT_DIR"), "/bindings.rs"));
+        assert!(
+            generated == expected,
+            "{} is out of date; refresh it with `update_pregenerated_bindings`",
+            pregenerated.display()
+        );
+    }
+
+    /// Writes the generated bindings over the checked-in bindings of this target. Run it
+    /// after updating Aeron with
+    /// `cargo test --features generate-bindings -- --ignored update_pregenerated_bindings`.
+    #[cfg(feature = "generate-bindings")]
+    #[test]
+    #[ignore]
+    fn update_pregenerated_bindings() {
+        let pregenerated = pregenerated_bindings_path();
+        std::fs::create_dir_all(pregenerated.parent().unwrap()).unwrap();
+        std::fs::write(
+            &pregenerated,
+            include_str!(concat!(env!("OUT_DIR"), "/bindings.rs")),
+        )
+        .expect("Couldn't update pre-generated bindings!");
+    }
 }
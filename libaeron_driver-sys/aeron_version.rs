@@ -0,0 +1 @@
+../libaeron-sys/aeron_version.rs
\ No newline at end of file
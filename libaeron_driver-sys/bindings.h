@@ -1,2 +1,15 @@
 #include <stddef.h>
 #include <aeron_driver.h>
+#include <aeron_flow_control.h>
+#include <aeron_congestion_control.h>
+#include <aeron_name_resolver.h>
+#include <media/aeron_udp_channel_transport_bindings.h>
+#include <media/aeron_udp_channel_transport_loss.h>
+
+// Defined by the loss interceptor but missing from its header. Calling it runs the interceptor's
+// one-time load of its parameters from the environment.
+int aeron_udp_channel_interceptor_loss_init_incoming(
+    void **interceptor_state, aeron_driver_context_t *context, aeron_udp_channel_transport_affinity_t affinity);
+#ifdef AERON_DRIVER_AGENT
+#include <agent/aeron_driver_agent.h>
+#endif
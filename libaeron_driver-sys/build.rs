@@ -1,6 +1,7 @@
 use cmake::Config;
 use dunce::canonicalize;
 use std::env;
+use std::fs;
 use std::path::{Path, PathBuf};
 
 pub enum LinkType {
@@ -32,43 +33,107 @@ impl LinkType {
     }
 }
 
-pub fn main() {
-    println!("cargo:rerun-if-changed=build.rs");
-    println!("cargo:rerun-if-changed=bindings.h");
+fn cmake_config(aeron_path: &Path) -> Config {
+    let mut config = Config::new(aeron_path);
+    config
+        .define("BUILD_AERON_DRIVER", "ON")
+        .define("BUILD_AERON_ARCHIVE_API", "OFF")
+        .define("AERON_TESTS", "OFF")
+        .define("AERON_BUILD_SAMPLES", "OFF")
+        .define("AERON_BUILD_DOCUMENTATION", "OFF");
+    config
+}
 
-    if pkg_config::probe_library("uuid").is_err() {
-        eprintln!("uuid lib not found in path");
+/// Finds an installed Aeron to use instead of building the `./aeron` submodule, returning
+/// the paths to generate bindings from. Set `AERON_LIB_DIR` and `AERON_INCLUDE_DIR` (a list
+/// of paths) to point at an installation, or enable the `system` feature to find it with
+/// pkg-config.
+fn system_aeron(link_type: &LinkType) -> Option<Vec<PathBuf>> {
+    println!("cargo:rerun-if-env-changed=AERON_LIB_DIR");
+    println!("cargo:rerun-if-env-changed=AERON_INCLUDE_DIR");
+
+    match (
+        env::var_os("AERON_LIB_DIR"),
+        env::var_os("AERON_INCLUDE_DIR"),
+    ) {
+        (Some(lib_dir), Some(include_dir)) => {
+            let lib_dir = PathBuf::from(lib_dir);
+            let include_paths: Vec<PathBuf> = env::split_paths(&include_dir).collect();
+            check_version(
+                installed_version(
+                    &include_paths,
+                    std::slice::from_ref(&lib_dir),
+                    "aeronmd.h",
+                    link_type.target_name(),
+                )
+                .as_deref(),
+            );
+            if cfg!(feature = "driver-agent")
+                && library_files(std::slice::from_ref(&lib_dir), "aeron_driver_agent")
+                    .next()
+                    .is_none()
+            {
+                panic!(
+                    "the `driver-agent` feature needs libaeron_driver_agent, which is not in AERON_LIB_DIR {}; install Aeron with the driver agent or build the bundled submodule instead",
+                    lib_dir.display()
+                );
+            }
+            println!("cargo:rustc-link-search=native={}", lib_dir.display());
+            println!(
+                "cargo:rustc-link-lib={}{}",
+                link_type.link_lib(),
+                link_type.target_name()
+            );
+            Some(include_paths)
+        }
+        (None, None) if !cfg!(feature = "system") => None,
+        (None, None) => {
+            let library = pkg_config::Config::new()
+                .statik(matches!(link_type, LinkType::Static))
+                .probe("aeron_driver")
+                .unwrap_or_else(|error| {
+                    panic!(
+                        "the `system` feature needs AERON_LIB_DIR and AERON_INCLUDE_DIR or a pkg-config entry for aeron_driver: {error}"
+                    )
+                });
+            let version = installed_version(
+                &library.include_paths,
+                &library.link_paths,
+                "aeronmd.h",
+                link_type.target_name(),
+            )
+            .or_else(|| Some(library.version.clone()).filter(|version| !version.is_empty()));
+            check_version(version.as_deref());
+            Some(library.include_paths)
+        }
+        _ => panic!("AERON_LIB_DIR and AERON_INCLUDE_DIR must be set together"),
     }
+}
 
+include!("aeron_version.rs");
+
+/// Builds the `./aeron` submodule with cmake, returning the paths to generate bindings from.
+fn build_submodule(link_type: &LinkType) -> Vec<PathBuf> {
     let aeron_path = canonicalize(Path::new("./aeron")).unwrap();
     let header_path = aeron_path.join("aeron-driver/src/main/c");
-    let out_path = PathBuf::from(env::var("OUT_DIR").unwrap());
 
-    let link_type = LinkType::detect();
     println!(
         "cargo:rustc-link-lib={}{}",
         link_type.link_lib(),
         link_type.target_name()
     );
 
-    if let LinkType::Static = link_type {
-        // On Windows, there are some extra libraries needed for static link
-        // that aren't included by Aeron.
-        if cfg!(target_os = "windows") {
-            println!("cargo:rustc-link-lib=shell32");
-            println!("cargo:rustc-link-lib=iphlpapi");
-        }
-    }
-
-    let cmake_output = Config::new(&aeron_path)
-        .define("BUILD_AERON_DRIVER", "ON")
-        .define("BUILD_AERON_ARCHIVE_API", "OFF")
-        .define("AERON_TESTS", "OFF")
-        .define("AERON_BUILD_SAMPLES", "OFF")
-        .define("AERON_BUILD_DOCUMENTATION", "OFF")
+    let cmake_output = cmake_config(&aeron_path)
         .build_target(link_type.target_name())
         .build();
 
+    if cfg!(feature = "driver-agent") {
+        // The event logging agent is only built as a shared library
+        cmake_config(&aeron_path)
+            .build_target("aeron_driver_agent")
+            .build();
+    }
+
     // Trying to figure out the final path is a bit weird;
     // For Linux/OSX, it's just build/lib
     // For Windows, the .lib file is in build/lib/{profile}, but the DLL
@@ -97,14 +162,84 @@ pub fn main() {
         base_lib_dir.join("binaries/Release").display()
     );
 
-    println!("cargo:include={}", header_path.display());
-    let bindings = bindgen::Builder::default()
-        .clang_arg(format!("-I{}", header_path.display()))
-        // We need to include some of the headers from `libaeron`, so update the include path here
-        .clang_arg(format!(
-            "-I{}",
-            aeron_path.join("aeron-client/src/main/c").display()
-        ))
+    // We need to include some of the headers from `libaeron`, so add its include path too
+    vec![header_path, aeron_path.join("aeron-client/src/main/c")]
+}
+
+pub fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=aeron_version.rs");
+    println!("cargo:rerun-if-changed=bindings.h");
+    // Fail before building Aeron when there are no bindings to use
+    #[cfg(not(feature = "generate-bindings"))]
+    check_pregenerated_bindings();
+
+    if pkg_config::probe_library("uuid").is_err() {
+        eprintln!("uuid lib not found in path");
+    }
+
+    let link_type = LinkType::detect();
+    let include_paths = system_aeron(&link_type).unwrap_or_else(|| build_submodule(&link_type));
+
+    if let LinkType::Static = link_type {
+        // The target, not the host the build script runs on, decides what to link
+        let target_os = env::var("CARGO_CFG_TARGET_OS").unwrap();
+        // On Windows, there are some extra libraries needed for static link
+        // that aren't included by Aeron.
+        if target_os == "windows" {
+            println!("cargo:rustc-link-lib=shell32");
+            println!("cargo:rustc-link-lib=iphlpapi");
+        }
+        // Elsewhere the system libraries the shared library would have pulled in must be
+        // linked explicitly, after the static archive.
+        if target_os == "linux" {
+            println!("cargo:rustc-link-lib=dylib=pthread");
+            println!("cargo:rustc-link-lib=dylib=dl");
+            println!("cargo:rustc-link-lib=dylib=m");
+        } else if target_os == "macos" {
+            println!("cargo:rustc-link-lib=dylib=pthread");
+        }
+    }
+
+    if cfg!(feature = "driver-agent") {
+        println!("cargo:rustc-link-lib=dylib=aeron_driver_agent");
+    }
+
+    if let Some(header_path) = include_paths.first() {
+        println!("cargo:include={}", header_path.display());
+    }
+    // Without bindgen, src/lib.rs includes the checked-in bindings of the target
+    #[cfg(feature = "generate-bindings")]
+    write_bindings(&include_paths);
+}
+
+#[cfg(not(feature = "generate-bindings"))]
+fn check_pregenerated_bindings() {
+    let target = format!(
+        "{}-{}",
+        env::var("CARGO_CFG_TARGET_ARCH").unwrap(),
+        env::var("CARGO_CFG_TARGET_OS").unwrap()
+    );
+    let bindings = Path::new("bindings").join(format!("{target}.rs"));
+    println!("cargo:rerun-if-changed={}", bindings.display());
+    if !bindings.is_file() {
+        panic!(
+            "no pre-generated bindings for {target} at {}; enable the `generate-bindings` feature, or write them with `just update-bindings` on that target",
+            bindings.display()
+        );
+    }
+}
+
+#[cfg(feature = "generate-bindings")]
+fn write_bindings(include_paths: &[PathBuf]) {
+    let mut builder = bindgen::Builder::default();
+    if cfg!(feature = "driver-agent") {
+        builder = builder.clang_arg("-DAERON_DRIVER_AGENT");
+    }
+    for include_path in include_paths {
+        builder = builder.clang_arg(format!("-I{}", include_path.display()));
+    }
+    let bindings = builder
         .header("bindings.h")
         .allowlist_function("aeron_.*")
         .allowlist_type("aeron_.*")
@@ -117,6 +252,6 @@ pub fn main() {
         .expect("Unable to generate aeron_driver bindings");
 
     bindings
-        .write_to_file(out_path.join("bindings.rs"))
+        .write_to_file(PathBuf::from(env::var("OUT_DIR").unwrap()).join("bindings.rs"))
         .expect("Couldn't write bindings!");
 }